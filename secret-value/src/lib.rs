@@ -10,6 +10,7 @@
 //!   of exposed secret values and clean-ups: `exposed_in_*`;
 //! - Supports deserialization of embedded JSON value in string using `expose_json_value_as`;
 //! - Securely encoding/decoding from hex/base64 formats;
+//! - Locking memory of values in RAM and excluding it from core dumps (`memlock` feature);
 //!
 //! # Working with the type:
 //!
@@ -56,7 +57,8 @@
 //! ```
 
 #![allow(unused_parens, unused_imports)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "memlock"), forbid(unsafe_code))]
+#![cfg_attr(feature = "memlock", deny(unsafe_code))]
 
 mod value;
 pub use value::*;
@@ -85,3 +87,9 @@ pub use hex_support::*;
 mod base64_support;
 #[cfg(feature = "base64")]
 pub use base64_support::*;
+
+#[cfg(feature = "memlock")]
+#[allow(unsafe_code)]
+mod memory_lock;
+#[cfg(feature = "memlock")]
+pub use memory_lock::*;
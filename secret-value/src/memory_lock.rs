@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const LOCKED_PAGES_SHARDS: usize = 64;
+
+// Locked pages can be shared between several allocations,
+// so pages are reference counted and locked/unlocked only by their first/last user.
+// Counters are sharded by page address to avoid contention between threads.
+static LOCKED_PAGES: [Mutex<BTreeMap<usize, usize>>; LOCKED_PAGES_SHARDS] =
+    [const { Mutex::new(BTreeMap::new()) }; LOCKED_PAGES_SHARDS];
+static LOCKED_PAGES_COUNT: AtomicUsize = AtomicUsize::new(0);
+static LOCK_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of bytes (rounded to memory pages) currently locked for secret values.
+pub fn locked_memory_bytes() -> usize {
+    LOCKED_PAGES_COUNT.load(Ordering::Relaxed) * page_size()
+}
+
+/// Number of attempts to lock memory that failed (e.g. because of insufficient `RLIMIT_MEMLOCK`).
+/// Secret values are still available in this case, but their memory isn't protected from swapping.
+pub fn locked_memory_failures() -> usize {
+    LOCK_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Memory pages of a buffer locked in RAM and excluded from core dumps.
+/// Pages are unlocked on drop, the buffer itself must be zeroized before that.
+/// No guard pages are placed around buffers: values are kept in regular heap allocations.
+#[derive(Debug, Default)]
+pub struct LockedMemoryRegion {
+    buffer: (usize, usize),
+    region: Option<(usize, usize)>,
+}
+
+impl LockedMemoryRegion {
+    pub fn lock(data: &[u8]) -> Self {
+        Self::lock_raw(data.as_ptr() as usize, data.len())
+    }
+
+    pub(crate) fn lock_raw(addr: usize, len: usize) -> Self {
+        if len == 0 {
+            return Self {
+                buffer: (addr, len),
+                region: None,
+            };
+        }
+
+        let page = page_size();
+        let start_page = addr & !(page - 1);
+        let end_page = (addr + len + page - 1) & !(page - 1);
+
+        for page_addr in (start_page..end_page).step_by(page) {
+            if !lock_page(page_addr, page) {
+                for locked_page_addr in (start_page..page_addr).step_by(page) {
+                    unlock_page(locked_page_addr, page);
+                }
+                LOCK_FAILURES.fetch_add(1, Ordering::Relaxed);
+                return Self {
+                    buffer: (addr, len),
+                    region: None,
+                };
+            }
+        }
+
+        Self {
+            buffer: (addr, len),
+            region: Some((start_page, end_page)),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.region.is_some()
+    }
+
+    /// Whether the region has been created for the buffer (it may have been reallocated since then).
+    pub(crate) fn is_for_buffer(&self, addr: usize, len: usize) -> bool {
+        self.buffer == (addr, len)
+    }
+
+    pub fn unlock(&mut self) {
+        if let Some((start_page, end_page)) = self.region.take() {
+            let page = page_size();
+            for page_addr in (start_page..end_page).step_by(page) {
+                unlock_page(page_addr, page);
+            }
+        }
+    }
+}
+
+impl Drop for LockedMemoryRegion {
+    fn drop(&mut self) {
+        self.unlock();
+    }
+}
+
+fn locked_pages_shard(page_addr: usize, page: usize) -> &'static Mutex<BTreeMap<usize, usize>> {
+    &LOCKED_PAGES[(page_addr / page) % LOCKED_PAGES_SHARDS]
+}
+
+fn lock_page(page_addr: usize, page: usize) -> bool {
+    let mut locked_pages = locked_pages_shard(page_addr, page).lock().unwrap();
+    match locked_pages.get_mut(&page_addr) {
+        Some(count) => *count += 1,
+        None => {
+            if !sys::lock_pages(page_addr, page) {
+                return false;
+            }
+            locked_pages.insert(page_addr, 1);
+            LOCKED_PAGES_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    true
+}
+
+fn unlock_page(page_addr: usize, page: usize) {
+    let mut locked_pages = locked_pages_shard(page_addr, page).lock().unwrap();
+    if let Some(count) = locked_pages.get_mut(&page_addr) {
+        *count -= 1;
+        if *count == 0 {
+            locked_pages.remove(&page_addr);
+            LOCKED_PAGES_COUNT.fetch_sub(1, Ordering::Relaxed);
+            sys::unlock_pages(page_addr, page);
+        }
+    }
+}
+
+fn page_size() -> usize {
+    static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+    match PAGE_SIZE.load(Ordering::Relaxed) {
+        0 => {
+            let page_size = sys::page_size();
+            PAGE_SIZE.store(page_size, Ordering::Relaxed);
+            page_size
+        }
+        page_size => page_size,
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    pub fn page_size() -> usize {
+        // SAFETY: sysconf has no memory safety preconditions.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page_size > 0 {
+            page_size as usize
+        } else {
+            4096
+        }
+    }
+
+    pub fn lock_pages(addr: usize, len: usize) -> bool {
+        // SAFETY: the range covers pages of a live allocation owned by the caller,
+        // mlock/madvise don't change the contents of memory.
+        unsafe {
+            if libc::mlock(addr as *const libc::c_void, len) != 0 {
+                return false;
+            }
+            #[cfg(target_os = "linux")]
+            libc::madvise(addr as *mut libc::c_void, len, libc::MADV_DONTDUMP);
+        }
+        true
+    }
+
+    pub fn unlock_pages(addr: usize, len: usize) {
+        // SAFETY: the range has been locked by `lock_pages` and still belongs to the process.
+        unsafe {
+            #[cfg(target_os = "linux")]
+            libc::madvise(addr as *mut libc::c_void, len, libc::MADV_DODUMP);
+            libc::munlock(addr as *const libc::c_void, len);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    pub fn page_size() -> usize {
+        4096
+    }
+
+    pub fn lock_pages(_addr: usize, _len: usize) -> bool {
+        false
+    }
+
+    pub fn unlock_pages(_addr: usize, _len: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecretValue;
+
+    // Probes mlock directly, without the accounting of this module
+    fn memory_locking_permitted() -> bool {
+        let page = page_size();
+        let probe = vec![0u8; 2 * page];
+        let page_addr = (probe.as_ptr() as usize + page - 1) & !(page - 1);
+        let permitted = sys::lock_pages(page_addr, page);
+        if permitted {
+            sys::unlock_pages(page_addr, page);
+        }
+        permitted
+    }
+
+    #[test]
+    fn locked_secret_value_test() {
+        let secret_value = SecretValue::from("test-secret-value".repeat(1024));
+        if !memory_locking_permitted() {
+            assert!(!secret_value.is_memory_locked());
+            assert!(locked_memory_failures() > 0);
+            return;
+        }
+
+        assert!(secret_value.is_memory_locked());
+        assert!(locked_memory_bytes() >= secret_value.as_sensitive_bytes().len());
+        let cloned_secret_value = secret_value.clone();
+        assert!(cloned_secret_value.is_memory_locked());
+        assert_eq!(
+            cloned_secret_value.as_sensitive_bytes(),
+            secret_value.as_sensitive_bytes()
+        );
+        assert!(!SecretValue::default().is_memory_locked());
+    }
+
+    #[test]
+    fn relocked_on_mutable_access_test() {
+        let mut secret_value = SecretValue::from("test-secret-value");
+        secret_value
+            .ref_sensitive_value_mut()
+            .extend_from_slice("test-secret-value".repeat(1024).as_bytes());
+        // The buffer has been reallocated outside of the locked memory
+        assert!(!secret_value.is_memory_locked());
+
+        secret_value.ref_sensitive_value_mut();
+        assert_eq!(secret_value.is_memory_locked(), memory_locking_permitted());
+    }
+
+    #[test]
+    fn shared_pages_lock_test() {
+        // A page that isn't shared with other allocations
+        let page = page_size();
+        let data = vec![1u8; 2 * page];
+        let page_offset = (page - data.as_ptr() as usize % page) % page;
+        let page_data = &data[page_offset..page_offset + page];
+
+        let mut first_region = LockedMemoryRegion::lock(page_data);
+        if !memory_locking_permitted() {
+            assert!(!first_region.is_locked());
+            return;
+        }
+        assert!(first_region.is_locked());
+        let second_region = LockedMemoryRegion::lock(page_data);
+        assert!(second_region.is_locked());
+
+        let page_addr = page_data.as_ptr() as usize;
+        let page_users = || {
+            locked_pages_shard(page_addr, page)
+                .lock()
+                .unwrap()
+                .get(&page_addr)
+                .copied()
+        };
+        assert_eq!(page_users(), Some(2));
+        first_region.unlock();
+        assert_eq!(page_users(), Some(1));
+        drop(second_region);
+        assert_eq!(page_users(), None);
+    }
+}
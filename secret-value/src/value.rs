@@ -3,22 +3,65 @@ use std::future::Future;
 use std::str::Utf8Error;
 use zeroize::*;
 
-#[derive(Zeroize, ZeroizeOnDrop, Eq, PartialEq, Default, Hash)]
-pub struct SecretValue(Vec<u8>);
+#[cfg(feature = "memlock")]
+use crate::memory_lock::LockedMemoryRegion as SecretMemoryLock;
+
+#[cfg(not(feature = "memlock"))]
+struct SecretMemoryLock;
+
+#[cfg(not(feature = "memlock"))]
+impl SecretMemoryLock {
+    fn lock_raw(_addr: usize, _len: usize) -> Self {
+        Self
+    }
+
+    fn is_locked(&self) -> bool {
+        false
+    }
+
+    fn is_for_buffer(&self, _addr: usize, _len: usize) -> bool {
+        true
+    }
+
+    fn unlock(&mut self) {}
+}
+
+pub struct SecretValue(Vec<u8>, SecretMemoryLock);
 
 impl SecretValue {
     pub fn new(src: Vec<u8>) -> Self {
-        Self(src)
+        let memory_lock = SecretMemoryLock::lock_raw(src.as_ptr() as usize, src.capacity());
+        Self(src, memory_lock)
     }
 
     pub fn ref_sensitive_value(&self) -> &Vec<u8> {
         &self.0
     }
 
+    /// With the `memlock` feature the buffer is relocked here if a previous mutable access
+    /// has reallocated it. Growing the value through the reference frees the previous buffer
+    /// without zeroizing it, so reserve the capacity up front or create a new value instead.
     pub fn ref_sensitive_value_mut(&mut self) -> &mut Vec<u8> {
+        self.relock_memory();
         &mut self.0
     }
 
+    /// Whether the value is locked in RAM and excluded from core dumps (requires `memlock` feature).
+    pub fn is_memory_locked(&self) -> bool {
+        self.1.is_locked()
+            && self
+                .1
+                .is_for_buffer(self.0.as_ptr() as usize, self.0.capacity())
+    }
+
+    fn relock_memory(&mut self) {
+        let (addr, len) = (self.0.as_ptr() as usize, self.0.capacity());
+        if !self.1.is_for_buffer(addr, len) {
+            self.1.unlock();
+            self.1 = SecretMemoryLock::lock_raw(addr, len);
+        }
+    }
+
     pub fn sensitive_value_to_str(&self) -> Result<&str, Utf8Error> {
         std::str::from_utf8(&self.0)
     }
@@ -111,7 +154,7 @@ impl SecretValue {
 
 impl From<String> for SecretValue {
     fn from(mut str: String) -> Self {
-        let result = Self(str.as_bytes().to_vec());
+        let result = Self::new(str.as_bytes().to_vec());
         str.zeroize();
         result
     }
@@ -119,7 +162,7 @@ impl From<String> for SecretValue {
 
 impl From<&mut String> for SecretValue {
     fn from(str: &mut String) -> Self {
-        let result = Self(str.as_bytes().to_vec());
+        let result = Self::new(str.as_bytes().to_vec());
         str.zeroize();
         result
     }
@@ -127,19 +170,19 @@ impl From<&mut String> for SecretValue {
 
 impl From<&Zeroizing<String>> for SecretValue {
     fn from(str: &Zeroizing<String>) -> Self {
-        Self(str.as_bytes().to_vec())
+        Self::new(str.as_bytes().to_vec())
     }
 }
 
 impl From<Vec<u8>> for SecretValue {
     fn from(vec: Vec<u8>) -> Self {
-        Self(vec)
+        Self::new(vec)
     }
 }
 
 impl From<&mut Vec<u8>> for SecretValue {
     fn from(vec: &mut Vec<u8>) -> Self {
-        let result = Self(vec.clone());
+        let result = Self::new(vec.clone());
         vec.zeroize();
         vec.clear();
         result
@@ -148,13 +191,49 @@ impl From<&mut Vec<u8>> for SecretValue {
 
 impl From<&Zeroizing<Vec<u8>>> for SecretValue {
     fn from(vec: &Zeroizing<Vec<u8>>) -> Self {
-        Self(vec.to_vec())
+        Self::new(vec.to_vec())
     }
 }
 
 impl From<&str> for SecretValue {
     fn from(str: &str) -> Self {
-        Self(str.as_bytes().to_vec())
+        Self::new(str.as_bytes().to_vec())
+    }
+}
+
+impl Zeroize for SecretValue {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretValue {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        // Unlocking memory before the buffer is deallocated
+        self.1.unlock();
+    }
+}
+
+impl ZeroizeOnDrop for SecretValue {}
+
+impl PartialEq for SecretValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for SecretValue {}
+
+impl std::hash::Hash for SecretValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Default for SecretValue {
+    fn default() -> Self {
+        Self::new(Vec::new())
     }
 }
 
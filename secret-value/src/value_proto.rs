@@ -8,7 +8,7 @@ impl prost::Message for SecretValue {
     where
         Self: Sized,
     {
-        if self.ref_sensitive_value().is_empty() {
+        if !self.ref_sensitive_value().is_empty() {
             prost::encoding::bytes::encode(1, self.ref_sensitive_value(), buf)
         }
     }
@@ -24,7 +24,11 @@ impl prost::Message for SecretValue {
         Self: Sized,
     {
         if tag == 1 {
-            prost::encoding::bytes::merge(wire_type, self.ref_sensitive_value_mut(), buf, ctx)
+            // Decoding into a separate buffer, so the value isn't reallocated in place
+            let mut decoded = zeroize::Zeroizing::new(Vec::new());
+            prost::encoding::bytes::merge(wire_type, &mut *decoded, buf, ctx)?;
+            *self = SecretValue::new(std::mem::take(&mut *decoded));
+            Ok(())
         } else {
             skip_field(wire_type, tag, buf, ctx)
         }
@@ -39,6 +43,25 @@ impl prost::Message for SecretValue {
     }
 
     fn clear(&mut self) {
-        self.ref_sensitive_value_mut().clear()
+        self.secure_clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn encode_decode_test() {
+        let secret_value = SecretValue::from("test-value");
+        let encoded = secret_value.encode_to_vec();
+        assert!(!encoded.is_empty());
+        assert_eq!(
+            SecretValue::decode(encoded.as_slice()).unwrap(),
+            secret_value
+        );
+
+        assert!(SecretValue::default().encode_to_vec().is_empty());
     }
 }
@@ -0,0 +1,187 @@
+//! # Derive macros for Secret Vault
+//!
+//! `#[derive(SecretVaultConfig)]` declares a typed secrets struct:
+//!
+//! ```rust,ignore
+//! #[derive(SecretVaultConfig)]
+//! struct AppSecrets {
+//!     #[secret(name = "db-password", namespace = "aws", version = "AWSCURRENT", auto_refresh)]
+//!     db_password: SecretValue,
+//!     #[secret(name = "api-port")]
+//!     api_port: u16,
+//!     // Optional fields are registered as non-required secrets
+//!     #[secret(name = "feature-token")]
+//!     feature_token: Option<String>,
+//! }
+//!
+//! let vault = SecretVaultBuilder::with_source(source)
+//!     .with_secret_refs(AppSecrets::secret_refs().iter().collect())
+//!     .build()?;
+//! vault.refresh().await?;
+//!
+//! let secrets = AppSecrets::load_from(&vault).await?;
+//! ```
+//!
+//! Field types must implement `SecretVaultConfigField`, which is available for `Secret`
+//! and all types implementing `SecretDecode`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type,
+};
+
+#[proc_macro_derive(SecretVaultConfig, attributes(secret))]
+pub fn secret_vault_config_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_secret_vault_config(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct SecretFieldAttrs {
+    name: Option<LitStr>,
+    namespace: Option<LitStr>,
+    version: Option<LitStr>,
+    auto_refresh: bool,
+    allow_in_snapshots: bool,
+}
+
+fn parse_secret_field_attrs(field: &syn::Field) -> syn::Result<SecretFieldAttrs> {
+    let mut attrs = SecretFieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("secret"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                attrs.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("namespace") {
+                attrs.namespace = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("version") {
+                attrs.version = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("auto_refresh") {
+                attrs.auto_refresh = true;
+            } else if meta.path.is_ident("allow_in_snapshots") {
+                attrs.allow_in_snapshots = true;
+            } else {
+                return Err(meta.error(
+                    "unsupported secret attribute, expected one of: name, namespace, version, auto_refresh, allow_in_snapshots",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "Option" {
+                return None;
+            }
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                    match args.args.first()? {
+                        GenericArgument::Type(inner_type) => Some(inner_type),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn expand_secret_vault_config(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "SecretVaultConfig can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "SecretVaultConfig can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut secret_refs = Vec::with_capacity(fields.len());
+    let mut field_loaders = Vec::with_capacity(fields.len());
+
+    for (index, field) in fields.iter().enumerate() {
+        let field_name = field.ident.as_ref().expect("named field");
+        let attrs = parse_secret_field_attrs(field)?;
+        let secret_name = attrs
+            .name
+            .unwrap_or_else(|| LitStr::new(&field_name.to_string(), field_name.span()));
+
+        let (required, field_type) = match option_inner_type(&field.ty) {
+            Some(inner_type) => (false, inner_type),
+            None => (true, &field.ty),
+        };
+
+        let namespace = attrs.namespace.map(|namespace| {
+            quote! { .with_namespace(::secret_vault::SecretNamespace::new(#namespace.to_string())) }
+        });
+        let version = attrs.version.map(|version| {
+            quote! { .with_secret_version(::secret_vault::SecretVersion::new(#version.to_string())) }
+        });
+        let auto_refresh = attrs.auto_refresh;
+        let allow_in_snapshots = attrs.allow_in_snapshots;
+
+        secret_refs.push(quote! {
+            ::secret_vault::SecretVaultRef::new(::secret_vault::SecretName::new(#secret_name.to_string()))
+                #namespace
+                #version
+                .with_required(#required)
+                .with_auto_refresh(#auto_refresh)
+                .with_allow_in_snapshots(#allow_in_snapshots)
+        });
+
+        field_loaders.push(if required {
+            quote! {
+                #field_name: <#field_type as ::secret_vault::SecretVaultConfigField>::require_field(view, &secret_refs[#index]).await?
+            }
+        } else {
+            quote! {
+                #field_name: <#field_type as ::secret_vault::SecretVaultConfigField>::load_field(view, &secret_refs[#index]).await?
+            }
+        });
+    }
+
+    Ok(quote! {
+        #[::secret_vault::async_trait::async_trait]
+        impl #impl_generics ::secret_vault::SecretVaultConfig for #struct_name #ty_generics #where_clause {
+            fn secret_refs() -> Vec<::secret_vault::SecretVaultRef> {
+                vec![#(#secret_refs),*]
+            }
+
+            async fn load_from<V>(view: &V) -> ::secret_vault::SecretVaultResult<Self>
+            where
+                V: ::secret_vault::SecretVaultView + Send + Sync,
+            {
+                let secret_refs = Self::secret_refs();
+                Ok(Self {
+                    #(#field_loaders),*
+                })
+            }
+        }
+    })
+}
@@ -97,6 +97,16 @@ fn criterion_benchmark(c: &mut Criterion) {
             .iter(|| vault_with_encryption_viewer.get_secret_by_ref(black_box(secret_ref)))
     });
 
+    c.bench_function("read-secrets-perf-encrypted-vault-with-secret", |b| {
+        b.to_async(criterion::async_executor::FuturesExecutor)
+            .iter(|| {
+                vault_with_encryption_viewer
+                    .with_secret_by_ref(black_box(secret_ref), |value, _| {
+                        value.ref_sensitive_value().len()
+                    })
+            })
+    });
+
     let vault_std_hash_snapshot = rt.block_on(async {
         vault_with_encryption
             .register_secret_refs(mock_store_keys.iter().collect())
@@ -113,6 +123,33 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("read-secrets-perf-snapshot", |b| {
         b.iter(|| vault_std_hash_snapshot.get_secret_by_ref(black_box(&secret_ref)))
     });
+
+    c.bench_function("read-secrets-perf-snapshot-with-secret", |b| {
+        b.iter(|| {
+            vault_std_hash_snapshot.with_secret_by_ref(black_box(secret_ref), |value, _| {
+                value.ref_sensitive_value().len()
+            })
+        })
+    });
+
+    let vault_encrypted_snapshot = rt.block_on(async {
+        vault_with_encryption
+            .snapshot(SecretVaultEncryptedSnapshotBuilder::new().unwrap())
+            .await
+            .unwrap()
+    });
+
+    c.bench_function("read-secrets-perf-encrypted-snapshot", |b| {
+        b.iter(|| vault_encrypted_snapshot.get_secret_by_ref(black_box(secret_ref)))
+    });
+
+    c.bench_function("read-secrets-perf-encrypted-snapshot-with-secret", |b| {
+        b.iter(|| {
+            vault_encrypted_snapshot.with_secret_by_ref(black_box(secret_ref), |value, _| {
+                value.ref_sensitive_value().len()
+            })
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
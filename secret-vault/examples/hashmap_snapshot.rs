@@ -31,5 +31,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     println!("Received secret: {:?}", secret_value);
 
+    // Snapshot which is updated automatically after each refresh
+    let auto_snapshot = vault.auto_snapshot().await?;
+    vault.refresh().await?;
+
+    println!(
+        "Received secret from auto snapshot: {:?}",
+        auto_snapshot.get_secret_by_ref(&secret_ref)?
+    );
+
     Ok(())
 }
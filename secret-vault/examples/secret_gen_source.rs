@@ -10,6 +10,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     let secret_ref1 = SecretVaultRef::new("my-temp-secret-pregen".into());
     let secret_ref2 = SecretVaultRef::new("my-temp-secret-autogen-on-refresh".into());
+    let secret_ref3 = SecretVaultRef::new("my-temp-password".into());
 
     // Building the vault with files source
     let vault = SecretVaultBuilder::with_source(TempSecretGenSource::with_options(
@@ -25,9 +26,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 TempSecretOptions::new(64)
                     .with_regenerate_on_refresh(true)
                     .with_printable(false),
+            )
+            .add_secret_generator(
+                &secret_ref3.key,
+                TempSecretOptions::new(0).with_kind(TempSecretGeneratorKind::Password(
+                    PasswordPolicy::new(24).with_symbols(true),
+                )),
             ),
     )?)
-    .with_secret_refs(vec![&secret_ref1, &secret_ref2])
+    .with_secret_refs(vec![&secret_ref1, &secret_ref2, &secret_ref3])
     .build()?;
 
     // Load secrets from the source
@@ -36,11 +43,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Reading the secret value
     let secret_value1: Secret = vault.require_secret_by_ref(&secret_ref1).await?;
     let secret_value2: Secret = vault.require_secret_by_ref(&secret_ref2).await?;
+    let secret_value3: Secret = vault.require_secret_by_ref(&secret_ref3).await?;
 
     println!(
-        "Received secret:\n{}\n{}",
+        "Received secret:\n{}\n{}\n{}",
         secret_value1.value.sensitive_value_to_str().unwrap(),
-        secret_value2.value.as_sensitive_hex_str().deref()
+        secret_value2.value.as_sensitive_hex_str().deref(),
+        secret_value3.value.sensitive_value_to_str().unwrap()
     );
 
     Ok(())
@@ -0,0 +1,72 @@
+use crate::agent::*;
+use crate::errors::*;
+use crate::*;
+use secret_vault_value::SecretValue;
+use std::path::Path;
+use tokio::net::UnixStream;
+
+/// A client for the secret vault agent.
+pub struct SecretVaultAgentClient {
+    stream: UnixStream,
+}
+
+impl SecretVaultAgentClient {
+    pub async fn connect<P: AsRef<Path>>(socket_path: P) -> SecretVaultResult<Self> {
+        let stream = UnixStream::connect(socket_path)
+            .await
+            .map_err(agent_io_error)?;
+        Ok(Self { stream })
+    }
+
+    pub async fn get_secret_by_ref(
+        &mut self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<SecretValue>> {
+        let response = self
+            .request(&SecretVaultAgentRequest::get_secret(secret_ref))
+            .await?;
+        match response.status() {
+            SecretVaultAgentResponseStatus::NotFound => Ok(None),
+            _ => Ok(response.value),
+        }
+    }
+
+    pub async fn list_secrets(&mut self) -> SecretVaultResult<Vec<SecretVaultAgentSecretMetadata>> {
+        Ok(self
+            .request(&SecretVaultAgentRequest::list_secrets())
+            .await?
+            .metadata)
+    }
+
+    /// Sends a request. Responses with error statuses other than `NotFound` are returned as errors.
+    pub async fn request(
+        &mut self,
+        request: &SecretVaultAgentRequest,
+    ) -> SecretVaultResult<SecretVaultAgentResponse> {
+        write_agent_frame(&mut self.stream, request).await?;
+
+        let response: SecretVaultAgentResponse =
+            read_agent_frame(&mut self.stream).await?.ok_or_else(|| {
+                SecretVaultSystemError::create(
+                    "AGENT_IO",
+                    "Agent closed the connection without a response",
+                )
+            })?;
+
+        match response.status() {
+            SecretVaultAgentResponseStatus::Ok | SecretVaultAgentResponseStatus::NotFound => {
+                Ok(response)
+            }
+            SecretVaultAgentResponseStatus::PermissionDenied => Err(
+                SecretVaultSystemError::create("AGENT_PERMISSION_DENIED", &response.message),
+            ),
+            SecretVaultAgentResponseStatus::InvalidRequest => Err(
+                SecretVaultInvalidParametersError::create("request", &response.message),
+            ),
+            SecretVaultAgentResponseStatus::Error => Err(SecretVaultSystemError::create(
+                "AGENT_ERROR",
+                &response.message,
+            )),
+        }
+    }
+}
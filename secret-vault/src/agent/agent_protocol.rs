@@ -0,0 +1,202 @@
+use crate::errors::*;
+use crate::*;
+use prost::Message;
+use rvstruct::ValueStruct;
+use secret_vault_value::SecretValue;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use zeroize::Zeroizing;
+
+/// Maximum size of a single protocol frame.
+pub const SECRET_VAULT_AGENT_MAX_FRAME_LEN: usize = 4 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum SecretVaultAgentRequestKind {
+    GetSecret = 0,
+    ListSecrets = 1,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SecretVaultAgentRequest {
+    #[prost(enumeration = "SecretVaultAgentRequestKind", tag = "1")]
+    pub kind: i32,
+    #[prost(string, tag = "2")]
+    pub secret_name: String,
+    #[prost(string, optional, tag = "3")]
+    pub namespace: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub secret_version: Option<String>,
+}
+
+impl SecretVaultAgentRequest {
+    pub fn get_secret(secret_ref: &SecretVaultRef) -> Self {
+        Self {
+            kind: SecretVaultAgentRequestKind::GetSecret.into(),
+            secret_name: secret_ref.key.secret_name.value().clone(),
+            namespace: secret_ref
+                .key
+                .namespace
+                .as_ref()
+                .map(|namespace| namespace.value().clone()),
+            secret_version: secret_ref
+                .key
+                .secret_version
+                .as_ref()
+                .map(|version| version.value().clone()),
+        }
+    }
+
+    pub fn list_secrets() -> Self {
+        Self {
+            kind: SecretVaultAgentRequestKind::ListSecrets.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn secret_vault_key(&self) -> SecretVaultKey {
+        SecretVaultKey::new(self.secret_name.clone().into())
+            .opt_namespace(self.namespace.clone().map(|namespace| namespace.into()))
+            .opt_secret_version(self.secret_version.clone().map(|version| version.into()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum SecretVaultAgentResponseStatus {
+    Ok = 0,
+    NotFound = 1,
+    PermissionDenied = 2,
+    InvalidRequest = 3,
+    Error = 4,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SecretVaultAgentSecretMetadata {
+    #[prost(string, tag = "1")]
+    pub secret_name: String,
+    #[prost(string, optional, tag = "2")]
+    pub namespace: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub secret_version: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub version: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub description: Option<String>,
+    #[prost(map = "string, string", tag = "6")]
+    pub labels: std::collections::HashMap<String, String>,
+    #[prost(int64, optional, tag = "7")]
+    pub created_at_millis: Option<i64>,
+    #[prost(int64, optional, tag = "8")]
+    pub updated_at_millis: Option<i64>,
+    #[prost(int64, tag = "9")]
+    pub cached_at_millis: i64,
+}
+
+impl From<&SecretMetadata> for SecretVaultAgentSecretMetadata {
+    fn from(metadata: &SecretMetadata) -> Self {
+        Self {
+            secret_name: metadata.key.secret_name.value().clone(),
+            namespace: metadata
+                .key
+                .namespace
+                .as_ref()
+                .map(|namespace| namespace.value().clone()),
+            secret_version: metadata
+                .key
+                .secret_version
+                .as_ref()
+                .map(|version| version.value().clone()),
+            version: metadata
+                .version
+                .as_ref()
+                .map(|version| version.value().clone()),
+            description: metadata.description.clone(),
+            labels: metadata
+                .labels
+                .iter()
+                .flatten()
+                .map(|label| (label.name.clone(), label.value.clone().unwrap_or_default()))
+                .collect(),
+            created_at_millis: metadata
+                .created_at
+                .map(|created_at| created_at.timestamp_millis()),
+            updated_at_millis: metadata
+                .updated_at
+                .map(|updated_at| updated_at.timestamp_millis()),
+            cached_at_millis: metadata.cached_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SecretVaultAgentResponse {
+    #[prost(enumeration = "SecretVaultAgentResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, tag = "2")]
+    pub message: String,
+    #[prost(message, optional, tag = "3")]
+    pub value: Option<SecretValue>,
+    #[prost(message, repeated, tag = "4")]
+    pub metadata: Vec<SecretVaultAgentSecretMetadata>,
+}
+
+impl SecretVaultAgentResponse {
+    pub fn with_status(status: SecretVaultAgentResponseStatus, message: &str) -> Self {
+        Self {
+            status: status.into(),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) async fn write_agent_frame<W, M>(writer: &mut W, message: &M) -> SecretVaultResult<()>
+where
+    W: AsyncWrite + Unpin,
+    M: Message,
+{
+    let frame_len = message.encoded_len();
+    let mut frame = Zeroizing::new(Vec::with_capacity(frame_len + 4));
+    frame.extend_from_slice(&(frame_len as u32).to_be_bytes());
+    message.encode(&mut *frame).map_err(|err| {
+        SecretVaultSystemError::create("AGENT_PROTOCOL", &format!("Unable to encode: {err}"))
+    })?;
+    writer.write_all(&frame).await.map_err(agent_io_error)?;
+    writer.flush().await.map_err(agent_io_error)
+}
+
+/// Reads the next frame. Returns `None` if the connection has been closed.
+pub(crate) async fn read_agent_frame<R, M>(reader: &mut R) -> SecretVaultResult<Option<M>>
+where
+    R: AsyncRead + Unpin,
+    M: Message + Default,
+{
+    let mut frame_len = [0u8; 4];
+    match reader.read_exact(&mut frame_len).await {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(agent_io_error(err)),
+    }
+
+    let frame_len = u32::from_be_bytes(frame_len) as usize;
+    if frame_len > SECRET_VAULT_AGENT_MAX_FRAME_LEN {
+        return Err(SecretVaultInvalidParametersError::create(
+            "frame",
+            &format!("Frame length {frame_len} exceeds the maximum length"),
+        ));
+    }
+
+    let mut frame = Zeroizing::new(vec![0u8; frame_len]);
+    reader
+        .read_exact(frame.as_mut_slice())
+        .await
+        .map_err(agent_io_error)?;
+
+    M::decode(frame.as_slice()).map(Some).map_err(|err| {
+        SecretVaultInvalidParametersError::create("frame", &format!("Invalid message: {err}"))
+    })
+}
+
+pub(crate) fn agent_io_error(err: std::io::Error) -> SecretVaultError {
+    SecretVaultSystemError::create("AGENT_IO", &format!("Agent socket error: {err}"))
+}
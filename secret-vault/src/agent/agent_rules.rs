@@ -0,0 +1,94 @@
+use crate::*;
+use rsb_derive::*;
+
+/// Credentials of a process connected to the agent, read with `SO_PEERCRED` (or its platform equivalent).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SecretVaultAgentPeer {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<i32>,
+}
+
+/// Grants access to secrets for processes running with any of the specified user or group ids.
+/// Secrets are matched by the namespace and the name if specified, so a rule without them
+/// grants access to all secrets exposed by the agent.
+/// Access is denied if no rules match a peer.
+#[derive(Debug, Clone, Eq, PartialEq, Builder)]
+pub struct SecretVaultAgentRule {
+    pub namespace: Option<SecretNamespace>,
+    pub secret_name: Option<SecretName>,
+    #[default = "Vec::new()"]
+    pub uids: Vec<u32>,
+    #[default = "Vec::new()"]
+    pub gids: Vec<u32>,
+}
+
+impl SecretVaultAgentRule {
+    pub fn allows(&self, peer: &SecretVaultAgentPeer, secret_vault_key: &SecretVaultKey) -> bool {
+        let secret_matches = self
+            .namespace
+            .as_ref()
+            .is_none_or(|namespace| secret_vault_key.namespace.as_ref() == Some(namespace))
+            && self
+                .secret_name
+                .as_ref()
+                .is_none_or(|secret_name| secret_name == &secret_vault_key.secret_name);
+
+        secret_matches && (self.uids.contains(&peer.uid) || self.gids.contains(&peer.gid))
+    }
+}
+
+pub(crate) fn agent_rules_allow(
+    rules: &[SecretVaultAgentRule],
+    peer: &SecretVaultAgentPeer,
+    secret_vault_key: &SecretVaultKey,
+) -> bool {
+    rules.iter().any(|rule| rule.allows(peer, secret_vault_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_rules_test() {
+        let peer = SecretVaultAgentPeer {
+            uid: 1000,
+            gid: 2000,
+            pid: None,
+        };
+        let rules = vec![
+            SecretVaultAgentRule::new()
+                .with_namespace("aws".into())
+                .with_uids(vec![1000]),
+            SecretVaultAgentRule::new()
+                .with_secret_name("db-password".into())
+                .with_gids(vec![2000]),
+        ];
+
+        assert!(agent_rules_allow(
+            &rules,
+            &peer,
+            &SecretVaultKey::new("api-key".into()).with_namespace("aws".into())
+        ));
+        assert!(agent_rules_allow(
+            &rules,
+            &peer,
+            &SecretVaultKey::new("db-password".into())
+        ));
+        assert!(!agent_rules_allow(
+            &rules,
+            &peer,
+            &SecretVaultKey::new("api-key".into())
+        ));
+        assert!(!agent_rules_allow(
+            &rules,
+            &SecretVaultAgentPeer {
+                uid: 0,
+                gid: 0,
+                pid: None
+            },
+            &SecretVaultKey::new("db-password".into())
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+use crate::agent::*;
+use crate::errors::*;
+use crate::*;
+use rvstruct::ValueStruct;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::*;
+
+/// Serves secrets from a vault view to local processes over a Unix domain socket.
+/// Only the registered secret refs are exposed, and each request is authorized with the agent rules.
+pub struct SecretVaultAgent<V>
+where
+    V: SecretVaultView + Send + Sync + 'static,
+{
+    view: Arc<V>,
+    secret_refs: Vec<SecretVaultRef>,
+    rules: Vec<SecretVaultAgentRule>,
+}
+
+impl<V> SecretVaultAgent<V>
+where
+    V: SecretVaultView + Send + Sync + 'static,
+{
+    pub fn new(view: V, secret_refs: Vec<&SecretVaultRef>) -> Self {
+        Self {
+            view: Arc::new(view),
+            secret_refs: secret_refs.into_iter().cloned().collect(),
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn with_rules(self, rules: Vec<SecretVaultAgentRule>) -> Self {
+        Self { rules, ..self }
+    }
+
+    pub fn add_rule(mut self, rule: SecretVaultAgentRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Binds the socket and starts accepting connections.
+    /// A stale socket file from a previous run is replaced.
+    pub async fn start<P: AsRef<Path>>(
+        self,
+        socket_path: P,
+    ) -> SecretVaultResult<SecretVaultAgentServer> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+
+        if let Ok(file_metadata) = std::fs::symlink_metadata(&socket_path) {
+            if file_metadata.file_type().is_socket() {
+                std::fs::remove_file(&socket_path).map_err(agent_io_error)?;
+            }
+        }
+
+        let listener = UnixListener::bind(&socket_path).map_err(agent_io_error)?;
+
+        info!(
+            "Secret vault agent is listening on {}. Exposed secrets: {}. Rules: {}",
+            socket_path.display(),
+            self.secret_refs.len(),
+            self.rules.len()
+        );
+
+        let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+        let handle = tokio::spawn(Self::accept_connections(
+            Arc::new(self),
+            listener,
+            shutdown_receiver,
+        ));
+
+        Ok(SecretVaultAgentServer {
+            socket_path,
+            shutdown_sender,
+            handle: Some(handle),
+        })
+    }
+
+    async fn accept_connections(
+        agent: Arc<Self>,
+        listener: UnixListener,
+        mut shutdown_receiver: watch::Receiver<bool>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = shutdown_receiver.changed() => {
+                    trace!("Exiting from secret vault agent accept loop...");
+                    break;
+                },
+                accepted = listener.accept() => {
+                    match accepted {
+                        Ok((stream, _)) => {
+                            tokio::spawn(Self::serve_connection(
+                                agent.clone(),
+                                stream,
+                                shutdown_receiver.clone(),
+                            ));
+                        }
+                        Err(err) => {
+                            warn!("Secret vault agent unable to accept a connection: {}", err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn serve_connection(
+        agent: Arc<Self>,
+        mut stream: UnixStream,
+        mut shutdown_receiver: watch::Receiver<bool>,
+    ) {
+        let peer = match stream.peer_cred() {
+            Ok(peer_cred) => SecretVaultAgentPeer {
+                uid: peer_cred.uid(),
+                gid: peer_cred.gid(),
+                pid: peer_cred.pid(),
+            },
+            Err(err) => {
+                warn!(
+                    "Secret vault agent unable to read peer credentials: {}",
+                    err
+                );
+                return;
+            }
+        };
+
+        loop {
+            let request = tokio::select! {
+                _ = shutdown_receiver.changed() => break,
+                request = read_agent_frame::<_, SecretVaultAgentRequest>(&mut stream) => request,
+            };
+
+            let response = match request {
+                Ok(Some(request)) => agent.handle_request(&peer, &request).await,
+                Ok(None) => break,
+                Err(err) => {
+                    // The connection can't be reused after invalid frames
+                    warn!(
+                        "Secret vault agent invalid request from {:?}: {}",
+                        peer, err
+                    );
+                    let response = SecretVaultAgentResponse::with_status(
+                        SecretVaultAgentResponseStatus::InvalidRequest,
+                        "Invalid request",
+                    );
+                    write_agent_frame(&mut stream, &response).await.ok();
+                    break;
+                }
+            };
+
+            if let Err(err) = write_agent_frame(&mut stream, &response).await {
+                debug!("Secret vault agent unable to send a response: {}", err);
+                break;
+            }
+        }
+    }
+
+    async fn handle_request(
+        &self,
+        peer: &SecretVaultAgentPeer,
+        request: &SecretVaultAgentRequest,
+    ) -> SecretVaultAgentResponse {
+        match SecretVaultAgentRequestKind::try_from(request.kind) {
+            Ok(SecretVaultAgentRequestKind::GetSecret) => {
+                let secret_vault_key = request.secret_vault_key();
+                let response = self.get_secret(peer, &secret_vault_key).await;
+                audit_agent_request(
+                    peer,
+                    SecretVaultAgentRequestKind::GetSecret,
+                    Some(&secret_vault_key),
+                    &response,
+                );
+                response
+            }
+            Ok(SecretVaultAgentRequestKind::ListSecrets) => {
+                let response = self.list_secrets(peer).await;
+                audit_agent_request(
+                    peer,
+                    SecretVaultAgentRequestKind::ListSecrets,
+                    None,
+                    &response,
+                );
+                response
+            }
+            Err(_) => SecretVaultAgentResponse::with_status(
+                SecretVaultAgentResponseStatus::InvalidRequest,
+                "Unknown request kind",
+            ),
+        }
+    }
+
+    async fn get_secret(
+        &self,
+        peer: &SecretVaultAgentPeer,
+        secret_vault_key: &SecretVaultKey,
+    ) -> SecretVaultAgentResponse {
+        if secret_vault_key.secret_name.value().is_empty() {
+            return SecretVaultAgentResponse::with_status(
+                SecretVaultAgentResponseStatus::InvalidRequest,
+                "Secret name is required",
+            );
+        }
+
+        if !agent_rules_allow(&self.rules, peer, secret_vault_key) {
+            return SecretVaultAgentResponse::with_status(
+                SecretVaultAgentResponseStatus::PermissionDenied,
+                "Access to the secret is denied",
+            );
+        }
+
+        let secret_ref = match self
+            .secret_refs
+            .iter()
+            .find(|secret_ref| &secret_ref.key == secret_vault_key)
+        {
+            Some(secret_ref) => secret_ref,
+            None => {
+                return SecretVaultAgentResponse::with_status(
+                    SecretVaultAgentResponseStatus::NotFound,
+                    "Secret not found",
+                )
+            }
+        };
+
+        match self.view.get_secret_by_ref(secret_ref).await {
+            Ok(Some(secret)) => SecretVaultAgentResponse {
+                status: SecretVaultAgentResponseStatus::Ok.into(),
+                metadata: vec![(&secret.metadata).into()],
+                value: Some(secret.value),
+                ..Default::default()
+            },
+            Ok(None) => SecretVaultAgentResponse::with_status(
+                SecretVaultAgentResponseStatus::NotFound,
+                "Secret not found",
+            ),
+            Err(SecretVaultError::AccessDeniedError(_)) => SecretVaultAgentResponse::with_status(
+                SecretVaultAgentResponseStatus::PermissionDenied,
+                "Access to the secret is denied",
+            ),
+            Err(err) => {
+                error!(
+                    "Secret vault agent unable to read secret {:?}: {}",
+                    secret_vault_key, err
+                );
+                SecretVaultAgentResponse::with_status(
+                    SecretVaultAgentResponseStatus::Error,
+                    "Unable to read the secret",
+                )
+            }
+        }
+    }
+
+    async fn list_secrets(&self, peer: &SecretVaultAgentPeer) -> SecretVaultAgentResponse {
+        let mut metadata = Vec::new();
+
+        for secret_ref in self
+            .secret_refs
+            .iter()
+            .filter(|secret_ref| agent_rules_allow(&self.rules, peer, &secret_ref.key))
+        {
+            match self.view.get_secret_metadata_by_ref(secret_ref).await {
+                Ok(Some(secret_metadata)) => {
+                    metadata.push(SecretVaultAgentSecretMetadata::from(&secret_metadata))
+                }
+                Ok(None) | Err(SecretVaultError::AccessDeniedError(_)) => {}
+                Err(err) => {
+                    error!(
+                        "Secret vault agent unable to read secret {:?}: {}",
+                        secret_ref.key, err
+                    );
+                    return SecretVaultAgentResponse::with_status(
+                        SecretVaultAgentResponseStatus::Error,
+                        "Unable to list secrets",
+                    );
+                }
+            }
+        }
+
+        SecretVaultAgentResponse {
+            status: SecretVaultAgentResponseStatus::Ok.into(),
+            metadata,
+            ..Default::default()
+        }
+    }
+}
+
+fn audit_agent_request(
+    peer: &SecretVaultAgentPeer,
+    kind: SecretVaultAgentRequestKind,
+    secret_vault_key: Option<&SecretVaultKey>,
+    response: &SecretVaultAgentResponse,
+) {
+    info!(
+        target: "secret_vault::agent::audit",
+        uid = peer.uid,
+        gid = peer.gid,
+        pid = ?peer.pid,
+        request = ?kind,
+        secret = ?secret_vault_key,
+        status = ?response.status(),
+        "Secret vault agent request"
+    );
+}
+
+/// A running agent. The socket file is removed on shutdown.
+pub struct SecretVaultAgentServer {
+    socket_path: PathBuf,
+    shutdown_sender: watch::Sender<bool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SecretVaultAgentServer {
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    pub async fn shutdown(&mut self) -> SecretVaultResult<()> {
+        debug!("Shutting down secret vault agent ...");
+        self.shutdown_sender.send_replace(true);
+
+        if let Some(handle) = self.handle.take() {
+            handle.await.expect("The task being joined has panicked");
+            std::fs::remove_file(&self.socket_path).map_err(agent_io_error)?;
+        }
+
+        info!("Secret vault agent has been shut down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secret_vault_value::SecretValue;
+    use std::os::unix::fs::MetadataExt;
+
+    #[tokio::test]
+    async fn agent_test() {
+        let socket_dir = tempfile::tempdir().unwrap();
+        let socket_path = socket_dir.path().join("secret-vault.sock");
+        let current_uid = std::fs::metadata(socket_dir.path()).unwrap().uid();
+
+        let allowed_ref = SecretVaultRef::new("db-password".into()).with_namespace("app".into());
+        let denied_ref = SecretVaultRef::new("admin-password".into());
+        let mock_secrets_store = MockSecretsSource::new(vec![
+            (allowed_ref.clone(), SecretValue::from("test-password")),
+            (denied_ref.clone(), SecretValue::from("admin-password")),
+        ]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store)
+            .with_secret_refs(vec![&allowed_ref, &denied_ref])
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        let mut server = SecretVaultAgent::new(vault.viewer(), vec![&allowed_ref, &denied_ref])
+            .add_rule(
+                SecretVaultAgentRule::new()
+                    .with_namespace("app".into())
+                    .with_uids(vec![current_uid]),
+            )
+            .start(&socket_path)
+            .await
+            .unwrap();
+
+        let mut client = SecretVaultAgentClient::connect(&socket_path).await.unwrap();
+
+        assert_eq!(
+            client.get_secret_by_ref(&allowed_ref).await.unwrap(),
+            Some(SecretValue::from("test-password"))
+        );
+        assert!(client.get_secret_by_ref(&denied_ref).await.is_err());
+        assert_eq!(
+            client
+                .get_secret_by_ref(
+                    &SecretVaultRef::new("unknown".into()).with_namespace("app".into())
+                )
+                .await
+                .unwrap(),
+            None
+        );
+
+        let metadata = client.list_secrets().await.unwrap();
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[0].secret_name, "db-password");
+        assert_eq!(metadata[0].namespace.as_deref(), Some("app"));
+
+        server.shutdown().await.unwrap();
+        assert!(!socket_path.exists());
+    }
+}
@@ -0,0 +1,17 @@
+//! Agent exposing secrets from a `SecretVaultView` to other local processes over a Unix domain socket.
+//!
+//! Every request and response is a protobuf message prefixed with its length as a big-endian `u32`.
+//! Multiple requests can be sent over the same connection.
+//! Access is authorized with the peer credentials of the connected process.
+
+mod agent_protocol;
+pub use agent_protocol::*;
+
+mod agent_rules;
+pub use agent_rules::*;
+
+mod agent_server;
+pub use agent_server::*;
+
+mod agent_client;
+pub use agent_client::*;
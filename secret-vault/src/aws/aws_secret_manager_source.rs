@@ -1,4 +1,5 @@
 use crate::errors::*;
+use crate::vault_tracing::*;
 use crate::*;
 use async_trait::*;
 use aws_sdk_secretsmanager::error::SdkError;
@@ -50,6 +51,32 @@ impl AwsSecretManagerSource {
             options: options.with_region(effective_region),
         })
     }
+
+    fn secret_arn(&self, secret_ref: &SecretVaultRef) -> String {
+        format!(
+            "arn:aws:secretsmanager:{}:{}:secret:{}",
+            self.options.region.as_ref().unwrap(),
+            self.options.account_id,
+            secret_ref.key.secret_name.value()
+        )
+    }
+}
+
+fn aws_secret_payload(
+    secret_value: &SecretValue,
+) -> (
+    Option<String>,
+    Option<aws_sdk_secretsmanager::primitives::Blob>,
+) {
+    match secret_value.sensitive_value_to_str() {
+        Ok(secret_str) => (Some(secret_str.to_string()), None),
+        Err(_) => (
+            None,
+            Some(aws_sdk_secretsmanager::primitives::Blob::new(
+                secret_value.ref_sensitive_value().clone(),
+            )),
+        ),
+    }
 }
 
 #[async_trait]
@@ -65,26 +92,23 @@ impl SecretsSource for AwsSecretManagerSource {
         let mut result_map: HashMap<SecretVaultRef, Secret> = HashMap::new();
 
         for secret_ref in references {
-            let aws_secret_arn = format!(
-                "arn:aws:secretsmanager:{}:{}:secret:{}",
-                self.options.region.as_ref().unwrap(),
-                self.options.account_id,
-                secret_ref.key.secret_name.value()
-            );
+            let aws_secret_arn = self.secret_arn(secret_ref);
 
-            match self
-                .client
-                .get_secret_value()
-                .secret_id(aws_secret_arn.clone())
-                .set_version_stage(
-                    secret_ref
-                        .key
-                        .secret_version
-                        .as_ref()
-                        .map(|v| v.value().into()),
-                )
-                .send()
-                .await
+            match traced(
+                source_secret_span("AmazonSecretManager", &secret_ref.key),
+                self.client
+                    .get_secret_value()
+                    .secret_id(aws_secret_arn.clone())
+                    .set_version_stage(
+                        secret_ref
+                            .key
+                            .secret_version
+                            .as_ref()
+                            .map(|v| v.value().into()),
+                    )
+                    .send(),
+            )
+            .await
             {
                 Ok(aws_secret) => {
                     let maybe_secret_value =
@@ -171,3 +195,371 @@ impl SecretsSource for AwsSecretManagerSource {
         Ok(result_map)
     }
 }
+
+#[async_trait]
+impl SecretsSink for AwsSecretManagerSource {
+    async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        match self.add_secret_version(secret_ref, secret_value).await {
+            Err(SecretVaultError::DataNotFoundError(_)) => {
+                debug!(
+                    "Secret {:?} doesn't exist yet, creating it",
+                    secret_ref.key.secret_name
+                );
+                let (secret_string, secret_binary) = aws_secret_payload(secret_value);
+                let aws_secret = self
+                    .client
+                    .create_secret()
+                    .name(secret_ref.key.secret_name.value())
+                    .set_secret_string(secret_string)
+                    .set_secret_binary(secret_binary)
+                    .send()
+                    .await?;
+
+                Ok(SecretMetadata::create_from_ref(secret_ref)
+                    .opt_version(aws_secret.version_id().map(|v| v.to_string().into())))
+            }
+            result => result,
+        }
+    }
+
+    async fn add_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let aws_secret_arn = self.secret_arn(secret_ref);
+        let (secret_string, secret_binary) = aws_secret_payload(secret_value);
+
+        match self
+            .client
+            .put_secret_value()
+            .secret_id(aws_secret_arn.clone())
+            .set_secret_string(secret_string)
+            .set_secret_binary(secret_binary)
+            .set_version_stages(
+                secret_ref
+                    .key
+                    .secret_version
+                    .as_ref()
+                    .map(|v| vec![v.value().clone()]),
+            )
+            .send()
+            .await
+        {
+            Ok(aws_secret) => Ok(SecretMetadata::create_from_ref(secret_ref)
+                .opt_version(aws_secret.version_id().map(|v| v.to_string().into()))),
+            Err(SdkError::ServiceError(svc_err))
+                if svc_err.err().is_resource_not_found_exception() =>
+            {
+                Err(SecretVaultError::DataNotFoundError(
+                    SecretVaultDataNotFoundError::new(
+                        SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
+                        format!("Secret doesn't exist to add a new version {aws_secret_arn}"),
+                    ),
+                ))
+            }
+            Err(err) => {
+                error!(
+                    "Unable to write secret version {}/{:?}: {}.",
+                    aws_secret_arn, &secret_ref.key.secret_version, err
+                );
+                Err(SecretVaultError::from(err))
+            }
+        }
+    }
+
+    async fn disable_secret_version(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        let version_stage = secret_ref.key.secret_version.as_ref().ok_or_else(|| {
+            SecretVaultInvalidParametersError::create(
+                "secret_version",
+                "Secret version stage must be specified to disable it",
+            )
+        })?;
+        let aws_secret_arn = self.secret_arn(secret_ref);
+
+        let aws_secret_desc = self
+            .client
+            .describe_secret()
+            .secret_id(aws_secret_arn.clone())
+            .send()
+            .await?;
+
+        let maybe_version_id = aws_secret_desc
+            .version_ids_to_stages()
+            .and_then(|versions| {
+                versions
+                    .iter()
+                    .find(|(_, stages)| stages.contains(version_stage.value()))
+            })
+            .map(|(version_id, _)| version_id.clone());
+
+        match maybe_version_id {
+            Some(version_id) => {
+                self.client
+                    .update_secret_version_stage()
+                    .secret_id(aws_secret_arn)
+                    .version_stage(version_stage.value())
+                    .remove_from_version_id(version_id)
+                    .send()
+                    .await?;
+            }
+            None => {
+                debug!(
+                    "Secret version stage {}/{:?} isn't attached to any version and it is skipped",
+                    aws_secret_arn, version_stage
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        self.client
+            .delete_secret()
+            .secret_id(self.secret_arn(secret_ref))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Adds a new version labelled only with the pending stage, so `AWSCURRENT` isn't moved.
+    /// Returns the AWS version id of the staged version.
+    async fn stage_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<Option<SecretVersion>> {
+        let metadata = self
+            .add_secret_version(
+                &secret_ref
+                    .clone()
+                    .with_secret_version(stages.pending.clone()),
+                secret_value,
+            )
+            .await?;
+        metadata.version.map(Some).ok_or_else(|| {
+            SecretVaultError::SecretsSourceError(SecretsSourceError::new(
+                SecretVaultErrorPublicGenericDetails::new("SECRET_VERSION_ID".into()),
+                format!(
+                    "AWS didn't return a version id for the staged secret {}",
+                    self.secret_arn(secret_ref)
+                ),
+            ))
+        })
+    }
+
+    /// Moves `AWSCURRENT` to the staged version (AWS moves `AWSPREVIOUS` to the replaced
+    /// version) and removes the pending stage from it.
+    async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let staged_version_id = match staged_version {
+            Some(staged_version) => staged_version,
+            None => return self.put_secret(secret_ref, secret_value).await,
+        };
+        let aws_secret_arn = self.secret_arn(secret_ref);
+
+        let aws_secret_desc = self
+            .client
+            .describe_secret()
+            .secret_id(aws_secret_arn.clone())
+            .send()
+            .await?;
+
+        let empty_versions = HashMap::new();
+        let stage_updates = aws_promotion_stage_updates(
+            aws_secret_desc
+                .version_ids_to_stages()
+                .unwrap_or(&empty_versions),
+            staged_version_id.value(),
+            stages,
+        );
+
+        for stage_update in stage_updates {
+            trace!(
+                "Updating AWS secret version stage {}: {:?}",
+                aws_secret_arn,
+                stage_update
+            );
+            self.client
+                .update_secret_version_stage()
+                .secret_id(aws_secret_arn.clone())
+                .version_stage(stage_update.version_stage)
+                .set_move_to_version_id(stage_update.move_to_version_id)
+                .set_remove_from_version_id(stage_update.remove_from_version_id)
+                .send()
+                .await?;
+        }
+
+        Ok(SecretMetadata::create_from_ref(secret_ref).with_version(staged_version_id.clone()))
+    }
+
+    /// Removes the pending stage from the staged version, so AWS deprecates it.
+    async fn discard_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<()> {
+        if let Some(staged_version_id) = staged_version {
+            self.client
+                .update_secret_version_stage()
+                .secret_id(self.secret_arn(secret_ref))
+                .version_stage(stages.pending.value())
+                .remove_from_version_id(staged_version_id.value())
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+const AWS_CURRENT_STAGE: &str = "AWSCURRENT";
+const AWS_PREVIOUS_STAGE: &str = "AWSPREVIOUS";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct AwsVersionStageUpdate {
+    version_stage: String,
+    move_to_version_id: Option<String>,
+    remove_from_version_id: Option<String>,
+}
+
+fn aws_promotion_stage_updates(
+    version_ids_to_stages: &HashMap<String, Vec<String>>,
+    staged_version_id: &str,
+    stages: &SecretVersionStages,
+) -> Vec<AwsVersionStageUpdate> {
+    let version_id_with_stage = |version_stage: &str| {
+        version_ids_to_stages
+            .iter()
+            .find(|(_, version_stages)| version_stages.iter().any(|s| s == version_stage))
+            .map(|(version_id, _)| version_id.clone())
+    };
+
+    let current_version_id = version_id_with_stage(AWS_CURRENT_STAGE)
+        .filter(|version_id| version_id != staged_version_id);
+
+    let mut stage_updates = vec![AwsVersionStageUpdate {
+        version_stage: AWS_CURRENT_STAGE.to_string(),
+        move_to_version_id: Some(staged_version_id.to_string()),
+        remove_from_version_id: current_version_id.clone(),
+    }];
+
+    if let Some(current_version_id) = current_version_id {
+        match &stages.previous {
+            Some(previous_stage) if previous_stage.value() != AWS_PREVIOUS_STAGE => stage_updates
+                .push(AwsVersionStageUpdate {
+                    version_stage: previous_stage.value().clone(),
+                    move_to_version_id: Some(current_version_id.clone()),
+                    remove_from_version_id: version_id_with_stage(previous_stage.value())
+                        .filter(|version_id| *version_id != current_version_id),
+                }),
+            Some(_) => {}
+            None => stage_updates.push(AwsVersionStageUpdate {
+                version_stage: AWS_PREVIOUS_STAGE.to_string(),
+                move_to_version_id: None,
+                remove_from_version_id: Some(current_version_id),
+            }),
+        }
+    }
+
+    stage_updates.push(AwsVersionStageUpdate {
+        version_stage: stages.pending.value().clone(),
+        move_to_version_id: None,
+        remove_from_version_id: Some(staged_version_id.to_string()),
+    });
+
+    stage_updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage_update(
+        version_stage: &str,
+        move_to_version_id: Option<&str>,
+        remove_from_version_id: Option<&str>,
+    ) -> AwsVersionStageUpdate {
+        AwsVersionStageUpdate {
+            version_stage: version_stage.to_string(),
+            move_to_version_id: move_to_version_id.map(|id| id.to_string()),
+            remove_from_version_id: remove_from_version_id.map(|id| id.to_string()),
+        }
+    }
+
+    #[test]
+    fn promotion_moves_stage_labels_test() {
+        let version_ids_to_stages = HashMap::from([
+            ("v1".to_string(), vec!["AWSPREVIOUS".to_string()]),
+            ("v2".to_string(), vec!["AWSCURRENT".to_string()]),
+            ("v3".to_string(), vec!["AWSPENDING".to_string()]),
+        ]);
+
+        assert_eq!(
+            aws_promotion_stage_updates(
+                &version_ids_to_stages,
+                "v3",
+                &SecretVersionStages::new("AWSPENDING".into()).with_previous("AWSPREVIOUS".into())
+            ),
+            vec![
+                stage_update("AWSCURRENT", Some("v3"), Some("v2")),
+                stage_update("AWSPENDING", None, Some("v3")),
+            ]
+        );
+
+        assert_eq!(
+            aws_promotion_stage_updates(
+                &version_ids_to_stages,
+                "v3",
+                &SecretVersionStages::new("AWSPENDING".into()).with_previous("OLD".into())
+            ),
+            vec![
+                stage_update("AWSCURRENT", Some("v3"), Some("v2")),
+                stage_update("OLD", Some("v2"), None),
+                stage_update("AWSPENDING", None, Some("v3")),
+            ]
+        );
+
+        assert_eq!(
+            aws_promotion_stage_updates(
+                &version_ids_to_stages,
+                "v3",
+                &SecretVersionStages::new("AWSPENDING".into())
+            ),
+            vec![
+                stage_update("AWSCURRENT", Some("v3"), Some("v2")),
+                stage_update("AWSPREVIOUS", None, Some("v2")),
+                stage_update("AWSPENDING", None, Some("v3")),
+            ]
+        );
+    }
+
+    #[test]
+    fn promotion_without_current_version_test() {
+        let version_ids_to_stages =
+            HashMap::from([("v1".to_string(), vec!["AWSPENDING".to_string()])]);
+
+        assert_eq!(
+            aws_promotion_stage_updates(
+                &version_ids_to_stages,
+                "v1",
+                &SecretVersionStages::new("AWSPENDING".into()).with_previous("AWSPREVIOUS".into())
+            ),
+            vec![
+                stage_update("AWSCURRENT", Some("v1"), None),
+                stage_update("AWSPENDING", None, Some("v1")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use clap::Parser;
+use secret_vault::errors::*;
+use secret_vault::*;
+use secret_vault_value::SecretValue;
+use std::process::ExitCode;
+use zeroize::Zeroizing;
+
+/// Inspects and tests secret vault configurations before deploying services.
+///
+/// Resolves secret refs from the configured sources and reports which of them are found or missing.
+/// Exits with a non-zero code if any required secret is missing or can't be decoded.
+#[derive(Debug, Parser)]
+#[command(name = "secret-vault", version)]
+struct CliArgs {
+    /// Vault configuration file (TOML, JSON or YAML with the `config-yaml` feature)
+    #[arg(long, short = 'c')]
+    config: Option<String>,
+
+    /// Source type if no config file is specified: env, files, aws-secretmanager, gcp-secretmanager
+    #[arg(long, default_value = "env")]
+    source: String,
+
+    /// Root path for the files source
+    #[arg(long)]
+    files_root: Option<String>,
+
+    /// AWS account id for the aws-secretmanager source
+    #[arg(long)]
+    aws_account_id: Option<String>,
+
+    /// AWS region for the aws-secretmanager source
+    #[arg(long)]
+    aws_region: Option<String>,
+
+    /// Google project id for the gcp-secretmanager source
+    #[arg(long)]
+    gcp_project_id: Option<String>,
+
+    /// Required secret to resolve in the format [namespace/]name[@version]
+    #[arg(long = "secret", short = 's')]
+    secrets: Vec<String>,
+
+    /// Optional secret to resolve in the format [namespace/]name[@version]
+    #[arg(long = "optional")]
+    optional_secrets: Vec<String>,
+
+    /// Validates that a secret can be decoded in the format [namespace/]name[@version]=type.
+    /// Types: string, int, uint, json, base64, hex, pem, pem-bundle, url
+    #[arg(long = "decode")]
+    decoders: Vec<String>,
+
+    /// Prints secret values (never printed by default)
+    #[arg(long)]
+    reveal: bool,
+}
+
+fn format_secret_key(key: &SecretVaultKey) -> String {
+    format!(
+        "{}{}{}",
+        key.namespace
+            .as_ref()
+            .map(|namespace| format!("{}/", namespace.as_ref()))
+            .unwrap_or_default(),
+        key.secret_name.as_ref(),
+        key.secret_version
+            .as_ref()
+            .map(|version| format!("@{}", version.as_ref()))
+            .unwrap_or_default()
+    )
+}
+
+/// Values which aren't valid UTF-8 are printed hex-encoded with a `hex:` prefix.
+fn format_revealed_value(secret_value: &SecretValue) -> Zeroizing<String> {
+    match secret_value.sensitive_value_to_str() {
+        Ok(str_value) => Zeroizing::new(str_value.to_string()),
+        Err(_) => Zeroizing::new(format!(
+            "hex:{}",
+            secret_value.as_sensitive_hex_str().as_str()
+        )),
+    }
+}
+
+fn config_from_args(args: &CliArgs) -> SecretVaultResult<SecretVaultFileConfig> {
+    match &args.config {
+        Some(config_path) => SecretVaultFileConfig::from_file(config_path),
+        None => {
+            let source = match args.source.as_str() {
+                "env" => SecretVaultSourceFileConfig::Env { namespace: None },
+                "files" => SecretVaultSourceFileConfig::Files {
+                    namespace: None,
+                    root_path: args.files_root.clone(),
+                },
+                "aws-secretmanager" => SecretVaultSourceFileConfig::AwsSecretmanager {
+                    namespace: None,
+                    account_id: args.aws_account_id.clone().ok_or_else(|| {
+                        SecretVaultInvalidParametersError::create(
+                            "aws-account-id",
+                            "AWS account id is required for the aws-secretmanager source",
+                        )
+                    })?,
+                    region: args.aws_region.clone(),
+                    read_metadata: true,
+                },
+                "gcp-secretmanager" => SecretVaultSourceFileConfig::GcpSecretmanager {
+                    namespace: None,
+                    project_id: args.gcp_project_id.clone().ok_or_else(|| {
+                        SecretVaultInvalidParametersError::create(
+                            "gcp-project-id",
+                            "Google project id is required for the gcp-secretmanager source",
+                        )
+                    })?,
+                    read_metadata: true,
+                },
+                other => {
+                    return Err(SecretVaultInvalidParametersError::create(
+                        "source",
+                        &format!("Unsupported source type '{other}'"),
+                    ))
+                }
+            };
+            let config = SecretVaultFileConfig {
+                sources: vec![source],
+                encryption: SecretVaultEncryptionFileConfig::None,
+                refresh: None,
+                retained_versions: 0,
+                secrets: Vec::new(),
+            };
+            config.validate()?;
+            Ok(config)
+        }
+    }
+}
+
+async fn validate_decoder<V>(
+    view: &V,
+    secret_ref: &SecretVaultRef,
+    decoder: &str,
+) -> SecretVaultResult<bool>
+where
+    V: SecretVaultView + Send + Sync,
+{
+    let decoded = match decoder {
+        "string" => view
+            .get_secret_as_by_ref::<String>(secret_ref)
+            .await?
+            .is_some(),
+        "int" => view
+            .get_secret_as_by_ref::<i64>(secret_ref)
+            .await?
+            .is_some(),
+        "uint" => view
+            .get_secret_as_by_ref::<u64>(secret_ref)
+            .await?
+            .is_some(),
+        "json" => view
+            .get_secret_as_by_ref::<SecretJson<serde_json::Value>>(secret_ref)
+            .await?
+            .is_some(),
+        "base64" => view
+            .get_secret_as_by_ref::<SecretBase64>(secret_ref)
+            .await?
+            .is_some(),
+        "hex" => view
+            .get_secret_as_by_ref::<SecretHex>(secret_ref)
+            .await?
+            .is_some(),
+        "pem" => view
+            .get_secret_as_by_ref::<SecretPem>(secret_ref)
+            .await?
+            .is_some(),
+        "pem-bundle" => view
+            .get_secret_as_by_ref::<SecretPemBundle>(secret_ref)
+            .await?
+            .is_some(),
+        #[cfg(feature = "url")]
+        "url" => view
+            .get_secret_as_by_ref::<url::Url>(secret_ref)
+            .await?
+            .is_some(),
+        other => {
+            return Err(SecretVaultInvalidParametersError::create(
+                "decode",
+                &format!("Unsupported decoder '{other}'"),
+            ))
+        }
+    };
+    Ok(decoded)
+}
+
+async fn run(args: CliArgs) -> SecretVaultResult<bool> {
+    let mut config = config_from_args(&args)?;
+
+    let mut secret_refs = config.secret_refs();
+    for secret in args.secrets.iter() {
+        secret_refs.push(secret.parse::<SecretVaultRef>()?);
+    }
+    for secret in args.optional_secrets.iter() {
+        secret_refs.push(secret.parse::<SecretVaultRef>()?.with_required(false));
+    }
+
+    let mut decoders = Vec::with_capacity(args.decoders.len());
+    for decoder in args.decoders.iter() {
+        let (secret, decoder_type) = decoder.rsplit_once('=').ok_or_else(|| {
+            SecretVaultInvalidParametersError::create(
+                "decode",
+                &format!("Invalid decoder '{decoder}', expected [namespace/]name[@version]=type"),
+            )
+        })?;
+        let secret_ref = secret.parse::<SecretVaultRef>()?;
+        if !secret_refs
+            .iter()
+            .any(|existing| existing.key == secret_ref.key)
+        {
+            secret_refs.push(secret_ref.clone());
+        }
+        decoders.push((secret_ref, decoder_type.to_string()));
+    }
+
+    // Missing secrets are reported instead of failing the refresh
+    config.secrets.clear();
+    let resolve_refs: Vec<SecretVaultRef> = secret_refs
+        .iter()
+        .map(|secret_ref| secret_ref.clone().with_required(false))
+        .collect();
+    let vault = SecretVaultBuilder::from_config(&config)
+        .await?
+        .with_secret_refs(resolve_refs.iter().collect())
+        .build()?;
+    vault.refresh().await?;
+
+    let mut success = true;
+
+    for secret_ref in secret_refs.iter() {
+        let required = if secret_ref.required {
+            "required"
+        } else {
+            "optional"
+        };
+        match vault.get_secret_by_ref(secret_ref).await? {
+            Some(secret) => {
+                println!(
+                    "FOUND    {} ({}): {:?}",
+                    format_secret_key(&secret_ref.key),
+                    required,
+                    secret.metadata
+                );
+                if args.reveal {
+                    println!(
+                        "         value: {}",
+                        format_revealed_value(&secret.value).as_str()
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "MISSING  {} ({})",
+                    format_secret_key(&secret_ref.key),
+                    required
+                );
+                if secret_ref.required {
+                    success = false;
+                }
+            }
+        }
+    }
+
+    for (secret_ref, decoder_type) in decoders.iter() {
+        match validate_decoder(&vault, secret_ref, decoder_type).await {
+            Ok(true) => println!(
+                "DECODED  {} as {}",
+                format_secret_key(&secret_ref.key),
+                decoder_type
+            ),
+            Ok(false) => {
+                println!(
+                    "MISSING  {} can't be decoded as {}",
+                    format_secret_key(&secret_ref.key),
+                    decoder_type
+                );
+                success = false;
+            }
+            Err(SecretVaultError::DecodeError(err)) => {
+                println!(
+                    "INVALID  {} as {}: {}",
+                    format_secret_key(&secret_ref.key),
+                    decoder_type,
+                    err.message
+                );
+                success = false;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(success)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    match run(CliArgs::parse()).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn run_test() {
+        std::env::set_var("SECRET_VAULT_CLI_TEST", "8080");
+
+        let args = CliArgs::parse_from([
+            "secret-vault",
+            "--secret",
+            "SECRET_VAULT_CLI_TEST",
+            "--decode",
+            "SECRET_VAULT_CLI_TEST=uint",
+        ]);
+        assert!(run(args).await.unwrap());
+
+        let args = CliArgs::parse_from([
+            "secret-vault",
+            "--optional",
+            "SECRET_VAULT_CLI_TEST_MISSING",
+            "--decode",
+            "SECRET_VAULT_CLI_TEST=pem",
+        ]);
+        assert!(!run(args).await.unwrap());
+
+        let args = CliArgs::parse_from(["secret-vault", "-s", "SECRET_VAULT_CLI_TEST_MISSING"]);
+        assert!(!run(args).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reveal_non_utf8_value_test() {
+        let secrets_dir = tempfile::tempdir().unwrap();
+        std::fs::write(secrets_dir.path().join("binary-secret"), [0xff, 0x00, 0x7f]).unwrap();
+
+        let args = CliArgs::parse_from([
+            "secret-vault",
+            "--source",
+            "files",
+            "--files-root",
+            secrets_dir.path().to_str().unwrap(),
+            "-s",
+            "binary-secret",
+            "--reveal",
+        ]);
+        assert!(run(args).await.unwrap());
+
+        assert_eq!(
+            format_revealed_value(&SecretValue::new(vec![0xff, 0x00, 0x7f])).as_str(),
+            "hex:ff007f"
+        );
+        assert_eq!(
+            format_revealed_value(&SecretValue::from("test-value")).as_str(),
+            "test-value"
+        );
+    }
+}
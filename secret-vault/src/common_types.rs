@@ -1,3 +1,4 @@
+use crate::errors::*;
 use chrono::prelude::*;
 use rsb_derive::*;
 use rvstruct::*;
@@ -92,6 +93,36 @@ impl SecretVaultRef {
     }
 }
 
+/// Parses secret refs in the format `[namespace/]name[@version]`.
+impl std::str::FromStr for SecretVaultRef {
+    type Err = SecretVaultError;
+
+    fn from_str(secret_ref: &str) -> Result<Self, Self::Err> {
+        let (secret_path, version) = match secret_ref.rsplit_once('@') {
+            Some((secret_path, version)) => (secret_path, Some(version)),
+            None => (secret_ref, None),
+        };
+        let (namespace, name) = match secret_path.split_once('/') {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, secret_path),
+        };
+
+        if name.is_empty()
+            || namespace.is_some_and(|namespace| namespace.is_empty())
+            || version.is_some_and(|version| version.is_empty())
+        {
+            return Err(SecretVaultInvalidParametersError::create(
+                "secret_ref",
+                &format!("Invalid secret ref '{secret_ref}', expected [namespace/]name[@version]"),
+            ));
+        }
+
+        Ok(SecretVaultRef::new(name.into())
+            .opt_namespace(namespace.map(|namespace| namespace.into()))
+            .opt_secret_version(version.map(|version| version.into())))
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
 pub struct SecretVaultKey {
     pub secret_name: SecretName,
@@ -113,6 +144,7 @@ pub struct SecretMetadataAnnotation {
 
 #[derive(Debug, Clone, Eq, PartialEq, Builder)]
 pub struct SecretMetadata {
+    /// When the current value has been cached. Refreshes returning the same value don't change it.
     #[default = "Utc::now()"]
     pub cached_at: DateTime<Utc>,
     pub key: SecretVaultKey,
@@ -6,11 +6,54 @@ use secret_vault_value::SecretValue;
 #[derive(Debug, Clone, Eq, PartialEq, ValueStruct)]
 pub struct EncryptedSecretValue(pub Vec<u8>);
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SecretVaultAadMode {
+    /// Name, namespace and version of a secret are bound to the encrypted value.
+    #[default]
+    FullKey,
+    /// Only the secret name is bound to the encrypted value (compatible with previous releases).
+    SecretNameOnly,
+}
+
 impl SecretVaultKey {
     #[inline]
     pub fn to_aad(&self) -> &String {
         self.secret_name.value()
     }
+
+    /// Unambiguous length-prefixed encoding of the key components for AEAD associated data.
+    pub fn to_canonical_aad(&self) -> Vec<u8> {
+        let components: [Option<&String>; 3] = [
+            Some(self.secret_name.value()),
+            self.namespace.as_ref().map(|namespace| namespace.value()),
+            self.secret_version.as_ref().map(|version| version.value()),
+        ];
+
+        let mut aad = Vec::with_capacity(
+            components
+                .iter()
+                .map(|component| 5 + component.map(|value| value.len()).unwrap_or(0))
+                .sum(),
+        );
+        for component in components {
+            match component {
+                Some(value) => {
+                    aad.push(1);
+                    aad.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    aad.extend_from_slice(value.as_bytes());
+                }
+                None => aad.push(0),
+            }
+        }
+        aad
+    }
+
+    pub fn to_aad_with_mode(&self, aad_mode: SecretVaultAadMode) -> Vec<u8> {
+        match aad_mode {
+            SecretVaultAadMode::FullKey => self.to_canonical_aad(),
+            SecretVaultAadMode::SecretNameOnly => self.to_aad().as_bytes().to_vec(),
+        }
+    }
 }
 
 #[async_trait]
@@ -26,6 +69,86 @@ pub trait SecretVaultEncryption {
         secret_vault_key: &SecretVaultKey,
         encrypted_secret_value: &EncryptedSecretValue,
     ) -> SecretVaultResult<SecretValue>;
+
+    /// Switches encryption of new values to a new key while keeping previous keys available for decryption.
+    /// Returns `false` if the encryption doesn't support key rotation.
+    async fn rotate_key(&self) -> SecretVaultResult<bool> {
+        Ok(false)
+    }
+
+    /// Removes keys retained by `rotate_key` after all values have been re-encrypted.
+    async fn retire_previous_keys(&self) -> SecretVaultResult<()> {
+        Ok(())
+    }
+
+    /// The id of the key new values are encrypted with (`None` if the encryption doesn't identify keys).
+    async fn current_key_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Removes keys retained by `rotate_key` that were rotated before the specified key,
+    /// so keys rotated automatically while values were re-encrypted are kept.
+    async fn retire_keys_before(&self, _key_id: u32) -> SecretVaultResult<()> {
+        self.retire_previous_keys().await
+    }
+
+    /// Returns `true` when previous keys are retained (e.g. after an automatic key rotation),
+    /// so values should be re-encrypted and the previous keys retired.
+    async fn requires_reencryption(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if values are stored as plain text, so decoded values may be cached next to them.
+    fn stores_plaintext(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait]
+impl SecretVaultEncryption for Box<dyn SecretVaultEncryption + Send + Sync> {
+    async fn encrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<EncryptedSecretValue> {
+        self.as_ref()
+            .encrypt_value(secret_vault_key, secret_value)
+            .await
+    }
+
+    async fn decrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        encrypted_secret_value: &EncryptedSecretValue,
+    ) -> SecretVaultResult<SecretValue> {
+        self.as_ref()
+            .decrypt_value(secret_vault_key, encrypted_secret_value)
+            .await
+    }
+
+    async fn rotate_key(&self) -> SecretVaultResult<bool> {
+        self.as_ref().rotate_key().await
+    }
+
+    async fn retire_previous_keys(&self) -> SecretVaultResult<()> {
+        self.as_ref().retire_previous_keys().await
+    }
+
+    async fn current_key_id(&self) -> Option<u32> {
+        self.as_ref().current_key_id().await
+    }
+
+    async fn retire_keys_before(&self, key_id: u32) -> SecretVaultResult<()> {
+        self.as_ref().retire_keys_before(key_id).await
+    }
+
+    async fn requires_reencryption(&self) -> bool {
+        self.as_ref().requires_reencryption().await
+    }
+
+    fn stores_plaintext(&self) -> bool {
+        self.as_ref().stores_plaintext()
+    }
 }
 
 #[derive(Debug)]
@@ -50,6 +173,10 @@ impl SecretVaultEncryption for SecretVaultNoEncryption {
     ) -> SecretVaultResult<SecretValue> {
         Ok(SecretValue::from(encrypted_secret_value.value().clone()))
     }
+
+    fn stores_plaintext(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(any(feature = "kms", feature = "ring-aead-encryption"))]
@@ -1,3 +1,4 @@
+use crate::SecretVaultKey;
 use rsb_derive::Builder;
 use std::error::Error;
 use std::fmt::Display;
@@ -9,6 +10,7 @@ pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SecretVaultError {
     SystemError(SecretVaultSystemError),
     DataNotFoundError(SecretVaultDataNotFoundError),
@@ -16,6 +18,8 @@ pub enum SecretVaultError {
     NetworkError(SecretVaultNetworkError),
     EncryptionError(SecretVaultEncryptionError),
     SecretsSourceError(SecretsSourceError),
+    DecodeError(SecretVaultDecodeError),
+    AccessDeniedError(SecretVaultAccessDeniedError),
 }
 
 impl Display for SecretVaultError {
@@ -27,6 +31,8 @@ impl Display for SecretVaultError {
             SecretVaultError::NetworkError(ref err) => err.fmt(f),
             SecretVaultError::EncryptionError(ref err) => err.fmt(f),
             SecretVaultError::SecretsSourceError(ref err) => err.fmt(f),
+            SecretVaultError::DecodeError(ref err) => err.fmt(f),
+            SecretVaultError::AccessDeniedError(ref err) => err.fmt(f),
         }
     }
 }
@@ -40,6 +46,8 @@ impl std::error::Error for SecretVaultError {
             SecretVaultError::NetworkError(ref err) => Some(err),
             SecretVaultError::EncryptionError(ref err) => Some(err),
             SecretVaultError::SecretsSourceError(ref err) => Some(err),
+            SecretVaultError::DecodeError(ref err) => Some(err),
+            SecretVaultError::AccessDeniedError(ref err) => Some(err),
         }
     }
 }
@@ -56,6 +64,15 @@ pub struct SecretVaultSystemError {
     pub message: String,
 }
 
+impl SecretVaultSystemError {
+    pub fn create(code: &str, message: &str) -> SecretVaultError {
+        SecretVaultError::SystemError(SecretVaultSystemError::new(
+            SecretVaultErrorPublicGenericDetails::new(code.to_string()),
+            message.to_string(),
+        ))
+    }
+}
+
 impl Display for SecretVaultSystemError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(
@@ -131,6 +148,14 @@ pub struct SecretVaultInvalidParametersError {
     pub public: SecretVaultInvalidParametersPublicDetails,
 }
 
+impl SecretVaultInvalidParametersError {
+    pub fn create(field: &str, error: &str) -> SecretVaultError {
+        SecretVaultError::InvalidParametersError(SecretVaultInvalidParametersError::new(
+            SecretVaultInvalidParametersPublicDetails::new(field.to_string(), error.to_string()),
+        ))
+    }
+}
+
 impl Display for SecretVaultInvalidParametersError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f, "Data not found error occurred: {:?}", self.public)
@@ -205,6 +230,65 @@ impl Display for SecretsSourceError {
 
 impl std::error::Error for SecretsSourceError {}
 
+/// Decoding a secret into a typed value failed.
+/// Identifies the secret, but never contains its content.
+#[derive(Debug, Eq, PartialEq, Clone, Builder)]
+pub struct SecretVaultDecodeError {
+    pub public: SecretVaultErrorPublicGenericDetails,
+    pub secret_key: SecretVaultKey,
+    pub message: String,
+}
+
+impl SecretVaultDecodeError {
+    pub fn create(secret_key: &SecretVaultKey, message: &str) -> SecretVaultError {
+        SecretVaultError::DecodeError(SecretVaultDecodeError::new(
+            SecretVaultErrorPublicGenericDetails::new("SECRET_DECODE".to_string()),
+            secret_key.clone(),
+            message.to_string(),
+        ))
+    }
+}
+
+impl Display for SecretVaultDecodeError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "SecretVault decode error: {:?} / {:?} / {}",
+            self.public, self.secret_key, self.message
+        )
+    }
+}
+
+impl std::error::Error for SecretVaultDecodeError {}
+
+/// Reading a secret isn't allowed by the access policy of a viewer.
+#[derive(Debug, Eq, PartialEq, Clone, Builder)]
+pub struct SecretVaultAccessDeniedError {
+    pub public: SecretVaultErrorPublicGenericDetails,
+    pub secret_key: SecretVaultKey,
+}
+
+impl SecretVaultAccessDeniedError {
+    pub fn create(secret_key: &SecretVaultKey) -> SecretVaultError {
+        SecretVaultError::AccessDeniedError(SecretVaultAccessDeniedError::new(
+            SecretVaultErrorPublicGenericDetails::new("SECRET_ACCESS_DENIED".to_string()),
+            secret_key.clone(),
+        ))
+    }
+}
+
+impl Display for SecretVaultAccessDeniedError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "SecretVault access denied: {:?} / {:?}",
+            self.public, self.secret_key
+        )
+    }
+}
+
+impl std::error::Error for SecretVaultAccessDeniedError {}
+
 #[cfg(feature = "gcp-base")]
 impl From<gcloud_sdk::error::Error> for SecretVaultError {
     fn from(e: gcloud_sdk::error::Error) -> Self {
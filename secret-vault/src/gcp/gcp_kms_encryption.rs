@@ -10,6 +10,7 @@ pub type GcpKmsKeyRef = kms_aead::providers::GcpKmsKeyRef;
 pub struct GcpKmsEnvelopeEncryption {
     envelope_aead_encryption:
         kms_aead::KmsAeadRingEnvelopeEncryption<kms_aead::providers::GcpKmsProvider>,
+    aad_mode: SecretVaultAadMode,
 }
 
 impl GcpKmsEnvelopeEncryption {
@@ -26,8 +27,13 @@ impl GcpKmsEnvelopeEncryption {
 
         Ok(Self {
             envelope_aead_encryption,
+            aad_mode: SecretVaultAadMode::default(),
         })
     }
+
+    pub fn with_aad_mode(self, aad_mode: SecretVaultAadMode) -> Self {
+        Self { aad_mode, ..self }
+    }
 }
 
 #[async_trait]
@@ -39,7 +45,10 @@ impl SecretVaultEncryption for GcpKmsEnvelopeEncryption {
     ) -> SecretVaultResult<EncryptedSecretValue> {
         let encrypted_value = self
             .envelope_aead_encryption
-            .encrypt_value(secret_vault_key.to_aad(), secret_value)
+            .encrypt_value(
+                &secret_vault_key.to_aad_with_mode(self.aad_mode),
+                secret_value,
+            )
             .await?;
 
         Ok(encrypted_value.into())
@@ -53,10 +62,16 @@ impl SecretVaultEncryption for GcpKmsEnvelopeEncryption {
         let secret_value = self
             .envelope_aead_encryption
             .decrypt_value(
-                secret_vault_key.to_aad(),
+                &secret_vault_key.to_aad_with_mode(self.aad_mode),
                 &encrypted_secret_value.clone().into(),
             )
             .await?;
         Ok(secret_value)
     }
+
+    // Every value is encrypted with its own KMS wrapped data encryption key,
+    // so re-encrypting values is enough to re-wrap them with new keys.
+    async fn rotate_key(&self) -> SecretVaultResult<bool> {
+        Ok(true)
+    }
 }
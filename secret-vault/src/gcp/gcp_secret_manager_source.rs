@@ -6,12 +6,19 @@ use std::collections::HashMap;
 
 use crate::errors::*;
 use crate::secrets_source::SecretsSource;
+use crate::vault_tracing::*;
 use crate::*;
 use tracing::*;
 
 use crate::prost_chrono::chrono_time_from_prost;
 use async_trait::*;
-use gcloud_sdk::google::cloud::secretmanager::v1::{AccessSecretVersionRequest, GetSecretRequest};
+use gcloud_sdk::google::cloud::secretmanager::v1::{
+    replication, AccessSecretVersionRequest, AddSecretVersionRequest, CreateSecretRequest,
+    DeleteSecretRequest, DisableSecretVersionRequest, GetSecretRequest, GetSecretVersionRequest,
+    Replication,
+};
+use gcloud_sdk::proto_ext::secretmanager::SecretPayload;
+use secret_vault_value::SecretValue;
 
 #[derive(Debug, Clone, Eq, PartialEq, Builder)]
 pub struct GcpSecretManagerSourceOptions {
@@ -49,6 +56,14 @@ impl GcpSecretManagerSource {
             options,
         })
     }
+
+    fn gcp_secret_path(&self, secret_ref: &SecretVaultRef) -> String {
+        format!(
+            "projects/{}/secrets/{}",
+            self.options.google_project_id,
+            secret_ref.key.secret_name.value()
+        )
+    }
 }
 
 #[async_trait]
@@ -78,15 +93,17 @@ impl SecretsSource for GcpSecretManagerSource {
             );
 
             trace!("Reading GCP secret: {}", gcp_secret_version_path);
-            let get_secret_response = self
-                .secret_manager_client
-                .get()
-                .access_secret_version(tonic::Request::new(AccessSecretVersionRequest {
-                    name: gcp_secret_version_path.clone(),
-                    ..Default::default()
-                }))
-                .await
-                .map_err(SecretVaultError::from);
+            let get_secret_response = traced(
+                source_secret_span("GoogleSecretManager", &secret_ref.key),
+                self.secret_manager_client
+                    .get()
+                    .access_secret_version(tonic::Request::new(AccessSecretVersionRequest {
+                        name: gcp_secret_version_path.clone(),
+                        ..Default::default()
+                    })),
+            )
+            .await
+            .map_err(SecretVaultError::from);
 
             match get_secret_response {
                 Ok(response) => {
@@ -163,6 +180,161 @@ impl SecretsSource for GcpSecretManagerSource {
     }
 }
 
+#[async_trait]
+impl SecretsSink for GcpSecretManagerSource {
+    async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        match self.add_secret_version(secret_ref, secret_value).await {
+            Err(SecretVaultError::DataNotFoundError(_)) => {
+                debug!(
+                    "Secret {:?} doesn't exist yet, creating it",
+                    secret_ref.key.secret_name
+                );
+                self.secret_manager_client
+                    .get()
+                    .create_secret(tonic::Request::new(CreateSecretRequest {
+                        parent: format!("projects/{}", self.options.google_project_id),
+                        secret_id: secret_ref.key.secret_name.value().clone(),
+                        secret: Some(gcloud_sdk::google::cloud::secretmanager::v1::Secret {
+                            replication: Some(Replication {
+                                replication: Some(replication::Replication::Automatic(
+                                    replication::Automatic::default(),
+                                )),
+                            }),
+                            ..Default::default()
+                        }),
+                    }))
+                    .await
+                    .map_err(SecretVaultError::from)?;
+
+                self.add_secret_version(secret_ref, secret_value).await
+            }
+            result => result,
+        }
+    }
+
+    async fn add_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let gcp_secret_path = self.gcp_secret_path(secret_ref);
+
+        trace!("Adding a new GCP secret version: {}", gcp_secret_path);
+        let gcp_secret_version = self
+            .secret_manager_client
+            .get()
+            .add_secret_version(tonic::Request::new(AddSecretVersionRequest {
+                parent: gcp_secret_path,
+                payload: Some(SecretPayload {
+                    data: secret_value.clone(),
+                    data_crc32c: None,
+                }),
+            }))
+            .await
+            .map_err(SecretVaultError::from)?
+            .into_inner();
+
+        let mut metadata = SecretMetadata::create_from_ref(secret_ref);
+        if let Some(version) = gcp_secret_version.name.rsplit('/').next() {
+            metadata.version(version.to_string().into());
+        }
+        metadata.created_at = gcp_secret_version
+            .create_time
+            .and_then(chrono_time_from_prost);
+
+        Ok(metadata)
+    }
+
+    async fn disable_secret_version(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        let gcp_secret_version = secret_ref.key.secret_version.as_ref().ok_or_else(|| {
+            SecretVaultInvalidParametersError::create(
+                "secret_version",
+                "Secret version must be specified to disable it",
+            )
+        })?;
+
+        self.secret_manager_client
+            .get()
+            .disable_secret_version(tonic::Request::new(DisableSecretVersionRequest {
+                name: format!(
+                    "{}/versions/{}",
+                    self.gcp_secret_path(secret_ref),
+                    gcp_secret_version.value()
+                ),
+                ..Default::default()
+            }))
+            .await
+            .map_err(SecretVaultError::from)?;
+
+        Ok(())
+    }
+
+    async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        self.secret_manager_client
+            .get()
+            .delete_secret(tonic::Request::new(DeleteSecretRequest {
+                name: self.gcp_secret_path(secret_ref),
+                ..Default::default()
+            }))
+            .await
+            .map_err(SecretVaultError::from)?;
+
+        Ok(())
+    }
+
+    /// GCP can't stage a version without making it `latest`, so nothing is written before
+    /// validation and the value is added as a new version only on promotion.
+    /// The replaced version is disabled if the previous version isn't kept.
+    async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        _staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let replaced_version_name = if stages.previous.is_none() {
+            match self
+                .secret_manager_client
+                .get()
+                .get_secret_version(tonic::Request::new(GetSecretVersionRequest {
+                    name: format!("{}/versions/latest", self.gcp_secret_path(secret_ref)),
+                }))
+                .await
+                .map_err(SecretVaultError::from)
+            {
+                Ok(gcp_secret_version) => Some(gcp_secret_version.into_inner().name),
+                Err(SecretVaultError::DataNotFoundError(_)) => None,
+                Err(err) => return Err(err),
+            }
+        } else {
+            None
+        };
+
+        let metadata = self.put_secret(secret_ref, secret_value).await?;
+
+        if let Some(replaced_version_name) = replaced_version_name {
+            trace!(
+                "Disabling replaced GCP secret version: {}",
+                replaced_version_name
+            );
+            self.secret_manager_client
+                .get()
+                .disable_secret_version(tonic::Request::new(DisableSecretVersionRequest {
+                    name: replaced_version_name,
+                    ..Default::default()
+                }))
+                .await
+                .map_err(SecretVaultError::from)?;
+        }
+
+        Ok(metadata)
+    }
+}
+
 fn from_google_expiration(
     gcp_expiration: gcloud_sdk::google::cloud::secretmanager::v1::secret::Expiration,
 ) -> SecretVaultResult<SecretExpiration> {
@@ -0,0 +1,7 @@
+mod secret_generator;
+pub use secret_generator::*;
+
+#[cfg(feature = "ring-aead-encryption")]
+mod ring_generators;
+#[cfg(feature = "ring-aead-encryption")]
+pub use ring_generators::*;
@@ -0,0 +1,470 @@
+use crate::errors::*;
+use crate::*;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::KeyPair;
+use rsb_derive::*;
+use secret_vault_value::SecretValue;
+use zeroize::Zeroizing;
+
+const LOWERCASE_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGIT_CHARS: &[u8] = b"0123456789";
+const SYMBOL_CHARS: &[u8] = b"!#$%&*+-=?@^_~";
+const ALPHANUMERIC_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const BASE64_URL_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_";
+
+pub struct RandomBytesSecretGenerator {
+    secure_rand: SystemRandom,
+    key_len: usize,
+    printable: bool,
+}
+
+impl RandomBytesSecretGenerator {
+    pub fn new(key_len: usize, printable: bool) -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+            key_len,
+            printable,
+        }
+    }
+}
+
+impl SecretGenerator for RandomBytesSecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        Ok(Secret::new(
+            generate_secret_value(&self.secure_rand, self.key_len, self.printable)?,
+            SecretMetadata::create_from_ref(secret_ref),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
+pub struct PasswordPolicy {
+    pub length: usize,
+
+    #[default = "true"]
+    pub lowercase: bool,
+
+    #[default = "true"]
+    pub uppercase: bool,
+
+    #[default = "true"]
+    pub digits: bool,
+
+    #[default = "false"]
+    pub symbols: bool,
+
+    #[default = "1"]
+    pub min_per_class: usize,
+}
+
+impl PasswordPolicy {
+    fn char_classes(&self) -> Vec<&'static [u8]> {
+        [
+            (self.lowercase, LOWERCASE_CHARS),
+            (self.uppercase, UPPERCASE_CHARS),
+            (self.digits, DIGIT_CHARS),
+            (self.symbols, SYMBOL_CHARS),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, chars)| chars)
+        .collect()
+    }
+}
+
+pub struct PasswordSecretGenerator {
+    secure_rand: SystemRandom,
+    policy: PasswordPolicy,
+}
+
+impl PasswordSecretGenerator {
+    pub fn new(policy: PasswordPolicy) -> SecretVaultResult<Self> {
+        let char_classes = policy.char_classes();
+        if char_classes.is_empty() {
+            return Err(SecretVaultInvalidParametersError::create(
+                "policy",
+                "Password policy must enable at least one character class",
+            ));
+        }
+        if policy.length < char_classes.len() * policy.min_per_class {
+            return Err(SecretVaultInvalidParametersError::create(
+                "length",
+                "Password length is too short to satisfy the character class requirements",
+            ));
+        }
+        Ok(Self {
+            secure_rand: SystemRandom::new(),
+            policy,
+        })
+    }
+}
+
+impl SecretGenerator for PasswordSecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        let char_classes = self.policy.char_classes();
+        let all_chars: Vec<u8> = char_classes.concat();
+
+        let mut password: Zeroizing<Vec<u8>> =
+            Zeroizing::new(Vec::with_capacity(self.policy.length));
+
+        for chars in char_classes.iter() {
+            for _ in 0..self.policy.min_per_class {
+                password.push(chars[random_below(&self.secure_rand, chars.len())?]);
+            }
+        }
+
+        while password.len() < self.policy.length {
+            password.push(all_chars[random_below(&self.secure_rand, all_chars.len())?]);
+        }
+
+        for idx in (1..password.len()).rev() {
+            let swap_idx = random_below(&self.secure_rand, idx + 1)?;
+            password.swap(idx, swap_idx);
+        }
+
+        Ok(Secret::new(
+            SecretValue::from(&password),
+            SecretMetadata::create_from_ref(secret_ref),
+        ))
+    }
+}
+
+pub struct Ed25519KeyPairSecretGenerator {
+    secure_rand: SystemRandom,
+}
+
+impl Ed25519KeyPairSecretGenerator {
+    pub fn new() -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+        }
+    }
+}
+
+impl SecretGenerator for Ed25519KeyPairSecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        let pkcs8_doc = ring::signature::Ed25519KeyPair::generate_pkcs8(&self.secure_rand)
+            .map_err(random_generator_error)?;
+        let key_pair =
+            ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8_doc.as_ref()).map_err(|e| {
+                SecretVaultError::SecretsSourceError(SecretsSourceError::new(
+                    SecretVaultErrorPublicGenericDetails::new("KEY_PAIR_GENERATOR".into()),
+                    format!("Unable to read generated key pair: {e}"),
+                ))
+            })?;
+
+        let mut metadata = SecretMetadata::create_from_ref(secret_ref);
+        metadata.add_annotation(
+            SecretMetadataAnnotation::new(PUBLIC_KEY_ANNOTATION.into())
+                .with_value(hex::encode(key_pair.public_key().as_ref())),
+        );
+
+        Ok(Secret::new(
+            SecretValue::new(pkcs8_doc.as_ref().to_vec()),
+            metadata,
+        ))
+    }
+}
+
+pub struct EcdsaP256KeyPairSecretGenerator {
+    secure_rand: SystemRandom,
+}
+
+impl EcdsaP256KeyPairSecretGenerator {
+    pub fn new() -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+        }
+    }
+}
+
+impl SecretGenerator for EcdsaP256KeyPairSecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        let signing_alg = &ring::signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+        let pkcs8_doc =
+            ring::signature::EcdsaKeyPair::generate_pkcs8(signing_alg, &self.secure_rand)
+                .map_err(random_generator_error)?;
+        let key_pair = ring::signature::EcdsaKeyPair::from_pkcs8(
+            signing_alg,
+            pkcs8_doc.as_ref(),
+            &self.secure_rand,
+        )
+        .map_err(|e| {
+            SecretVaultError::SecretsSourceError(SecretsSourceError::new(
+                SecretVaultErrorPublicGenericDetails::new("KEY_PAIR_GENERATOR".into()),
+                format!("Unable to read generated key pair: {e}"),
+            ))
+        })?;
+
+        let mut metadata = SecretMetadata::create_from_ref(secret_ref);
+        metadata.add_annotation(
+            SecretMetadataAnnotation::new(PUBLIC_KEY_ANNOTATION.into())
+                .with_value(hex::encode(key_pair.public_key().as_ref())),
+        );
+
+        Ok(Secret::new(
+            SecretValue::new(pkcs8_doc.as_ref().to_vec()),
+            metadata,
+        ))
+    }
+}
+
+pub const PUBLIC_KEY_ANNOTATION: &str = "public_key";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TokenAlphabet {
+    Base64Url,
+    Alphanumeric,
+}
+
+impl TokenAlphabet {
+    fn chars(&self) -> &'static [u8] {
+        match self {
+            TokenAlphabet::Base64Url => BASE64_URL_CHARS,
+            TokenAlphabet::Alphanumeric => ALPHANUMERIC_CHARS,
+        }
+    }
+}
+
+pub struct TokenSecretGenerator {
+    secure_rand: SystemRandom,
+    length: usize,
+    alphabet: TokenAlphabet,
+}
+
+impl TokenSecretGenerator {
+    pub fn new(length: usize, alphabet: TokenAlphabet) -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+            length,
+            alphabet,
+        }
+    }
+}
+
+impl SecretGenerator for TokenSecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        let chars = self.alphabet.chars();
+        let mut token: Zeroizing<Vec<u8>> = Zeroizing::new(Vec::with_capacity(self.length));
+        while token.len() < self.length {
+            token.push(chars[random_below(&self.secure_rand, chars.len())?]);
+        }
+
+        Ok(Secret::new(
+            SecretValue::from(&token),
+            SecretMetadata::create_from_ref(secret_ref),
+        ))
+    }
+}
+
+pub struct UuidV4SecretGenerator {
+    secure_rand: SystemRandom,
+}
+
+impl UuidV4SecretGenerator {
+    pub fn new() -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+        }
+    }
+}
+
+impl SecretGenerator for UuidV4SecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        let mut uuid_data: Zeroizing<[u8; 16]> = Zeroizing::new([0u8; 16]);
+        self.secure_rand
+            .fill(uuid_data.as_mut())
+            .map_err(random_generator_error)?;
+
+        uuid_data[6] = (uuid_data[6] & 0x0f) | 0x40;
+        uuid_data[8] = (uuid_data[8] & 0x3f) | 0x80;
+
+        let uuid_hex = Zeroizing::new(hex::encode(uuid_data.as_ref()));
+        Ok(Secret::new(
+            SecretValue::from(format!(
+                "{}-{}-{}-{}-{}",
+                &uuid_hex[0..8],
+                &uuid_hex[8..12],
+                &uuid_hex[12..16],
+                &uuid_hex[16..20],
+                &uuid_hex[20..32]
+            )),
+            SecretMetadata::create_from_ref(secret_ref),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum HmacKeyAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HmacKeyAlgorithm {
+    pub fn ring_algorithm(&self) -> ring::hmac::Algorithm {
+        match self {
+            HmacKeyAlgorithm::Sha256 => ring::hmac::HMAC_SHA256,
+            HmacKeyAlgorithm::Sha384 => ring::hmac::HMAC_SHA384,
+            HmacKeyAlgorithm::Sha512 => ring::hmac::HMAC_SHA512,
+        }
+    }
+
+    pub fn key_len(&self) -> usize {
+        self.ring_algorithm().digest_algorithm().output_len()
+    }
+}
+
+pub struct HmacKeySecretGenerator {
+    secure_rand: SystemRandom,
+    algorithm: HmacKeyAlgorithm,
+}
+
+impl HmacKeySecretGenerator {
+    pub fn new(algorithm: HmacKeyAlgorithm) -> Self {
+        Self {
+            secure_rand: SystemRandom::new(),
+            algorithm,
+        }
+    }
+}
+
+impl SecretGenerator for HmacKeySecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        Ok(Secret::new(
+            generate_secret_value(&self.secure_rand, self.algorithm.key_len(), false)?,
+            SecretMetadata::create_from_ref(secret_ref),
+        ))
+    }
+}
+
+pub(crate) fn random_below(
+    secure_rand: &SystemRandom,
+    upper_bound: usize,
+) -> SecretVaultResult<usize> {
+    let upper_bound = upper_bound as u32;
+    let zone = u32::MAX - (u32::MAX % upper_bound);
+    loop {
+        let mut rand_data = [0u8; 4];
+        secure_rand
+            .fill(&mut rand_data)
+            .map_err(random_generator_error)?;
+        let rand_value = u32::from_be_bytes(rand_data);
+        if rand_value < zone {
+            return Ok((rand_value % upper_bound) as usize);
+        }
+    }
+}
+
+pub(crate) fn random_generator_error(e: ring::error::Unspecified) -> SecretVaultError {
+    SecretVaultError::SecretsSourceError(
+        SecretsSourceError::new(
+            SecretVaultErrorPublicGenericDetails::new(format!(
+                "Unable to initialise random key: {e:?}"
+            )),
+            format!("Unable to initialise random key: {e}"),
+        )
+        .with_root_cause(Box::new(e)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_policy_test() {
+        let secret_ref = SecretVaultRef::new("test-password".into());
+        let generator = PasswordSecretGenerator::new(
+            PasswordPolicy::new(24)
+                .with_symbols(true)
+                .with_min_per_class(2),
+        )
+        .unwrap();
+
+        for _ in 0..100 {
+            let secret = generator.generate_secret(&secret_ref).unwrap();
+            let password = secret.value.as_sensitive_bytes();
+            assert_eq!(password.len(), 24);
+            for chars in [LOWERCASE_CHARS, UPPERCASE_CHARS, DIGIT_CHARS, SYMBOL_CHARS] {
+                assert!(password.iter().filter(|c| chars.contains(c)).count() >= 2);
+            }
+        }
+
+        assert!(PasswordSecretGenerator::new(PasswordPolicy::new(2)).is_err());
+    }
+
+    #[test]
+    fn ed25519_key_pair_test() {
+        let secret_ref = SecretVaultRef::new("test-key-pair".into());
+        let secret = Ed25519KeyPairSecretGenerator::new()
+            .generate_secret(&secret_ref)
+            .unwrap();
+
+        let key_pair =
+            ring::signature::Ed25519KeyPair::from_pkcs8(secret.value.as_sensitive_bytes()).unwrap();
+        assert_eq!(
+            secret
+                .metadata
+                .annotations
+                .unwrap()
+                .into_iter()
+                .find(|annotation| annotation.name == PUBLIC_KEY_ANNOTATION)
+                .and_then(|annotation| annotation.value),
+            Some(hex::encode(key_pair.public_key().as_ref()))
+        );
+    }
+
+    #[test]
+    fn ecdsa_p256_key_pair_test() {
+        let secret_ref = SecretVaultRef::new("test-key-pair".into());
+        let secret = EcdsaP256KeyPairSecretGenerator::new()
+            .generate_secret(&secret_ref)
+            .unwrap();
+
+        let key_pair = ring::signature::EcdsaKeyPair::from_pkcs8(
+            &ring::signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            secret.value.as_sensitive_bytes(),
+            &SystemRandom::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            secret
+                .metadata
+                .annotations
+                .unwrap()
+                .into_iter()
+                .find(|annotation| annotation.name == PUBLIC_KEY_ANNOTATION)
+                .and_then(|annotation| annotation.value),
+            Some(hex::encode(key_pair.public_key().as_ref()))
+        );
+    }
+
+    #[test]
+    fn token_and_uuid_test() {
+        let secret_ref = SecretVaultRef::new("test-token".into());
+
+        for alphabet in [TokenAlphabet::Base64Url, TokenAlphabet::Alphanumeric] {
+            let secret = TokenSecretGenerator::new(40, alphabet)
+                .generate_secret(&secret_ref)
+                .unwrap();
+            let token = secret.value.as_sensitive_bytes();
+            assert_eq!(token.len(), 40);
+            assert!(token.iter().all(|c| alphabet.chars().contains(c)));
+        }
+
+        let uuid = UuidV4SecretGenerator::new()
+            .generate_secret(&secret_ref)
+            .unwrap();
+        let uuid_str = uuid.value.sensitive_value_to_str().unwrap();
+        assert_eq!(uuid_str.len(), 36);
+        assert_eq!(&uuid_str[14..15], "4");
+        assert!("89ab".contains(&uuid_str[19..20]));
+
+        let hmac_key = HmacKeySecretGenerator::new(HmacKeyAlgorithm::Sha384)
+            .generate_secret(&secret_ref)
+            .unwrap();
+        assert_eq!(hmac_key.value.as_sensitive_bytes().len(), 48);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{Secret, SecretVaultRef, SecretVaultResult};
+
+/// Generates new secret values, used to rotate secrets and by temporary secret sources.
+pub trait SecretGenerator {
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret>;
+}
+
+impl<F> SecretGenerator for F
+where
+    F: Fn(&SecretVaultRef) -> SecretVaultResult<Secret>,
+{
+    fn generate_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Secret> {
+        self(secret_ref)
+    }
+}
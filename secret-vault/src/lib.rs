@@ -16,6 +16,15 @@
 //! - Automatic refresh secrets from the sources support (optional);
 //! - Multi-sources support;
 //! - Snapshots for performance-critical secrets;
+//! - Writing secrets back to the sources supporting it (optional);
+//! - Secret rotation with generators and validation hooks (optional);
+//! - Typed access to secrets with built-in decoders (strings, integers, JSON, base64/hex, PEM, URLs);
+//! - Declaring typed secrets structs with `#[derive(SecretVaultConfig)]` (optional);
+//! - Declarative vault configuration from TOML/YAML/JSON files (optional);
+//! - Rendering templates with secret placeholders;
+//! - Serving secrets to local processes over a Unix domain socket (optional);
+//! - Exporting secrets to files (e.g. tmpfs) for other processes;
+//! - Audit trail of secret reads and vault changes;
 //!
 //! ```rust,ignore
 //!
@@ -75,9 +84,14 @@ pub mod errors;
 mod secrets_source;
 pub use secrets_source::*;
 
+mod secrets_sink;
+pub use secrets_sink::*;
+
 mod simple_sources;
 pub use simple_sources::*;
 
+mod secret_fingerprint;
+
 mod vault_store;
 
 mod common_types;
@@ -86,6 +100,9 @@ pub use common_types::*;
 #[cfg(feature = "ring-aead-encryption")]
 pub mod ring_encryption;
 
+#[cfg(feature = "rustcrypto-aead-encryption")]
+mod rustcrypto_aead;
+
 #[cfg(feature = "gcp-base")]
 pub mod gcp;
 
@@ -103,6 +120,9 @@ pub use vault_builder::SecretVaultBuilder;
 mod vault_viewer;
 pub use vault_viewer::*;
 
+mod vault_access_policy;
+pub use vault_access_policy::*;
+
 mod snapshot;
 pub use snapshot::*;
 
@@ -115,5 +135,50 @@ pub use vault_auto_refresher::*;
 mod multiple_sources;
 pub use multiple_sources::*;
 
+mod generators;
+pub use generators::*;
+
+mod vault_rotator;
+pub use vault_rotator::*;
+
+mod secret_decode;
+pub use secret_decode::*;
+
+mod vault_config;
+pub use vault_config::*;
+
+mod vault_template;
+pub use vault_template::*;
+
+mod vault_files_exporter;
+pub use vault_files_exporter::*;
+
+mod vault_audit;
+pub use vault_audit::*;
+
+mod vault_refresh_report;
+pub use vault_refresh_report::*;
+
+mod vault_metrics;
+mod vault_tracing;
+
+#[cfg(feature = "derive")]
+pub use secret_vault_derive::SecretVaultConfig;
+
+// Allows using derive macros inside the crate itself
+#[cfg(feature = "derive")]
+extern crate self as secret_vault;
+
+#[doc(hidden)]
+pub use async_trait;
+
+#[cfg(feature = "config")]
+mod vault_file_config;
+#[cfg(feature = "config")]
+pub use vault_file_config::*;
+
+#[cfg(all(feature = "agent", unix))]
+pub mod agent;
+
 #[cfg(feature = "gcp-base")]
 mod prost_chrono;
@@ -1,7 +1,10 @@
+use crate::vault_metrics::*;
+use crate::vault_tracing::*;
 use crate::*;
 use async_trait::*;
 use rvstruct::ValueStruct;
 use std::collections::HashMap;
+use std::time::Instant;
 
 pub struct MultipleSecretsSources {
     sources: HashMap<SecretNamespace, Box<dyn SecretsSource + Send + Sync>>,
@@ -51,19 +54,22 @@ impl SecretsSource for MultipleSecretsSources {
     ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
         let mut result_map: HashMap<SecretVaultRef, Secret> = HashMap::new();
         for (namespace, source) in self.sources.iter() {
-            let source_references: Vec<SecretVaultRef> = references
-                .iter()
-                .filter(|reference| {
-                    reference
-                        .key
-                        .namespace
-                        .iter()
-                        .any(|ref_namespace| *ref_namespace == *namespace)
-                })
-                .cloned()
-                .collect();
-
-            let mut source_secrets = source.get_secrets(&source_references).await?;
+            let source_references = namespace_references(namespace, references);
+
+            let source_name = format!("{}:{}", namespace.value(), source.name());
+            let span = source_span(&source_name, source_references.len());
+            let started_at = Instant::now();
+            let source_secrets = traced(span.clone(), source.get_secrets(&source_references)).await;
+            record_refresh(
+                &source_name,
+                started_at.elapsed(),
+                refresh_outcome(&source_secrets),
+            );
+            if let Ok(source_secrets) = &source_secrets {
+                span.record("found", source_secrets.len());
+            }
+
+            let mut source_secrets = source_secrets?;
             for (secret_ref, secret) in source_secrets.drain() {
                 result_map.insert(secret_ref, secret);
             }
@@ -71,6 +77,59 @@ impl SecretsSource for MultipleSecretsSources {
 
         Ok(result_map)
     }
+
+    async fn get_secrets_partially(
+        &self,
+        references: &[SecretVaultRef],
+    ) -> HashMap<SecretVaultRef, SecretVaultResult<Option<Secret>>> {
+        let mut result_map: HashMap<SecretVaultRef, SecretVaultResult<Option<Secret>>> =
+            HashMap::new();
+        for (namespace, source) in self.sources.iter() {
+            let source_references = namespace_references(namespace, references);
+
+            let source_name = format!("{}:{}", namespace.value(), source.name());
+            let span = source_span(&source_name, source_references.len());
+            let started_at = Instant::now();
+            let source_results = traced(
+                span.clone(),
+                source.get_secrets_partially(&source_references),
+            )
+            .await;
+            record_refresh(
+                &source_name,
+                started_at.elapsed(),
+                partial_refresh_outcome(&source_results),
+            );
+            span.record(
+                "found",
+                source_results
+                    .values()
+                    .filter(|result| matches!(result, Ok(Some(_))))
+                    .count(),
+            );
+
+            result_map.extend(source_results);
+        }
+
+        result_map
+    }
+}
+
+fn namespace_references(
+    namespace: &SecretNamespace,
+    references: &[SecretVaultRef],
+) -> Vec<SecretVaultRef> {
+    references
+        .iter()
+        .filter(|reference| {
+            reference
+                .key
+                .namespace
+                .iter()
+                .any(|ref_namespace| *ref_namespace == *namespace)
+        })
+        .cloned()
+        .collect()
 }
 
 #[cfg(test)]
@@ -1,32 +1,225 @@
+use crate::errors::*;
 use crate::{SecretVaultKey, SecretVaultResult};
 
 use async_trait::async_trait;
 use kms_aead::ring_encryption::RingAeadEncryption;
 use kms_aead::*;
 use ring::rand::SystemRandom;
+use rvstruct::ValueStruct;
 use secret_vault_value::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use tracing::*;
 
 use crate::encryption::*;
 
+const KEY_ID_LEN: usize = 4;
+
+/// Variants depend on enabled features, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SecretVaultAeadAlgorithm {
+    Aes256Gcm,
+    ChaCha20Poly1305,
+    #[cfg(feature = "rustcrypto-aead-encryption")]
+    XChaCha20Poly1305,
+    #[cfg(feature = "rustcrypto-aead-encryption")]
+    Aes256GcmSiv,
+}
+
+impl SecretVaultAeadAlgorithm {
+    /// Number of encryptions with one key and random nonces before the key is automatically rotated.
+    /// 96-bit nonces follow the NIST SP 800-38D bound of 2^32 invocations,
+    /// extended nonces and nonce-misuse resistant algorithms allow many more.
+    pub fn max_encryptions_per_key(&self) -> u64 {
+        match self {
+            SecretVaultAeadAlgorithm::Aes256Gcm | SecretVaultAeadAlgorithm::ChaCha20Poly1305 => {
+                1 << 32
+            }
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadAlgorithm::XChaCha20Poly1305
+            | SecretVaultAeadAlgorithm::Aes256GcmSiv => 1 << 48,
+        }
+    }
+
+    /// Number of encryptions before automatic rotation for any ring algorithm.
+    /// Algorithms without a variant here (e.g. AES-128-GCM) use 96-bit nonces as well.
+    fn max_encryptions_per_ring_key(algo: &'static ring::aead::Algorithm) -> u64 {
+        if algo == &ring::aead::CHACHA20_POLY1305 {
+            SecretVaultAeadAlgorithm::ChaCha20Poly1305.max_encryptions_per_key()
+        } else {
+            SecretVaultAeadAlgorithm::Aes256Gcm.max_encryptions_per_key()
+        }
+    }
+}
+
+enum SecretVaultAeadCipher {
+    Ring(RingAeadEncryption),
+    #[cfg(feature = "rustcrypto-aead-encryption")]
+    RustCrypto(SecretVaultAeadAlgorithm, SystemRandom),
+}
+
+impl SecretVaultAeadCipher {
+    fn generate_data_encryption_key(&self) -> SecretVaultResult<DataEncryptionKey> {
+        match self {
+            SecretVaultAeadCipher::Ring(ring_encryption) => {
+                Ok(ring_encryption.generate_data_encryption_key()?)
+            }
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadCipher::RustCrypto(_, secure_rand) => {
+                crate::rustcrypto_aead::generate_data_encryption_key(secure_rand)
+            }
+        }
+    }
+
+    async fn encrypt_value(
+        &self,
+        aad: &Vec<u8>,
+        secret_value: &SecretValue,
+        encryption_key: &DataEncryptionKey,
+    ) -> SecretVaultResult<Vec<u8>> {
+        match self {
+            SecretVaultAeadCipher::Ring(ring_encryption) => Ok(ring_encryption
+                .encrypt_value(aad, secret_value, encryption_key)
+                .await?
+                .0),
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadCipher::RustCrypto(algorithm, secure_rand) => {
+                crate::rustcrypto_aead::encrypt_value(
+                    *algorithm,
+                    secure_rand,
+                    aad,
+                    secret_value,
+                    encryption_key,
+                )
+            }
+        }
+    }
+
+    async fn decrypt_value(
+        &self,
+        aad: &Vec<u8>,
+        cipher_text: &[u8],
+        encryption_key: &DataEncryptionKey,
+    ) -> SecretVaultResult<SecretValue> {
+        match self {
+            SecretVaultAeadCipher::Ring(ring_encryption) => Ok(ring_encryption
+                .decrypt_value(aad, &CipherText(cipher_text.to_vec()), encryption_key)
+                .await?),
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadCipher::RustCrypto(algorithm, _) => {
+                crate::rustcrypto_aead::decrypt_value(*algorithm, aad, cipher_text, encryption_key)
+            }
+        }
+    }
+}
+
+struct SecretVaultRingAeadKeys {
+    current_key_id: u32,
+    current_key: DataEncryptionKey,
+    current_key_encryptions: AtomicU64,
+    previous_keys: Vec<(u32, DataEncryptionKey)>,
+}
+
+impl SecretVaultRingAeadKeys {
+    fn rotate(&mut self, new_key: DataEncryptionKey) {
+        let new_key_id = self.current_key_id.wrapping_add(1);
+        let previous_key = std::mem::replace(&mut self.current_key, new_key);
+        let previous_key_id = std::mem::replace(&mut self.current_key_id, new_key_id);
+        self.previous_keys.push((previous_key_id, previous_key));
+        self.current_key_encryptions = AtomicU64::new(0);
+        debug!(
+            "Vault encryption key has been rotated to key id: {}",
+            new_key_id
+        );
+    }
+}
+
 pub struct SecretVaultRingAeadEncryption {
-    ring_encryption: RingAeadEncryption,
-    vault_secret: DataEncryptionKey,
+    cipher: SecretVaultAeadCipher,
+    keys: RwLock<SecretVaultRingAeadKeys>,
+    aad_mode: SecretVaultAadMode,
+    max_encryptions_per_key: u64,
 }
 
 impl SecretVaultRingAeadEncryption {
     pub fn new() -> SecretVaultResult<Self> {
-        Self::with_algorithm(&ring::aead::AES_256_GCM)
+        Self::with_aead_algorithm(SecretVaultAeadAlgorithm::Aes256Gcm)
     }
 
     pub fn with_algorithm(algo: &'static ring::aead::Algorithm) -> SecretVaultResult<Self> {
-        let ring_encryption = RingAeadEncryption::with_algorithm(algo, SystemRandom::new())?;
-        let vault_secret = ring_encryption.generate_data_encryption_key()?;
+        Self::with_cipher(
+            SecretVaultAeadCipher::Ring(RingAeadEncryption::with_algorithm(
+                algo,
+                SystemRandom::new(),
+            )?),
+            SecretVaultAeadAlgorithm::max_encryptions_per_ring_key(algo),
+        )
+    }
+
+    pub fn with_aead_algorithm(algorithm: SecretVaultAeadAlgorithm) -> SecretVaultResult<Self> {
+        let cipher = match algorithm {
+            SecretVaultAeadAlgorithm::Aes256Gcm => SecretVaultAeadCipher::Ring(
+                RingAeadEncryption::with_algorithm(&ring::aead::AES_256_GCM, SystemRandom::new())?,
+            ),
+            SecretVaultAeadAlgorithm::ChaCha20Poly1305 => {
+                SecretVaultAeadCipher::Ring(RingAeadEncryption::with_algorithm(
+                    &ring::aead::CHACHA20_POLY1305,
+                    SystemRandom::new(),
+                )?)
+            }
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadAlgorithm::XChaCha20Poly1305
+            | SecretVaultAeadAlgorithm::Aes256GcmSiv => {
+                SecretVaultAeadCipher::RustCrypto(algorithm, SystemRandom::new())
+            }
+        };
+        Self::with_cipher(cipher, algorithm.max_encryptions_per_key())
+    }
+
+    fn with_cipher(
+        cipher: SecretVaultAeadCipher,
+        max_encryptions_per_key: u64,
+    ) -> SecretVaultResult<Self> {
+        let vault_secret = cipher.generate_data_encryption_key()?;
 
         Ok(Self {
-            ring_encryption,
-            vault_secret,
+            cipher,
+            keys: RwLock::new(SecretVaultRingAeadKeys {
+                current_key_id: 1,
+                current_key: vault_secret,
+                current_key_encryptions: AtomicU64::new(0),
+                previous_keys: Vec::new(),
+            }),
+            aad_mode: SecretVaultAadMode::default(),
+            max_encryptions_per_key,
         })
     }
+
+    pub fn with_aad_mode(self, aad_mode: SecretVaultAadMode) -> Self {
+        Self { aad_mode, ..self }
+    }
+
+    /// Overrides the number of encryptions with one key before it is automatically rotated.
+    pub fn with_max_encryptions_per_key(self, max_encryptions_per_key: u64) -> Self {
+        Self {
+            max_encryptions_per_key,
+            ..self
+        }
+    }
+
+    async fn rotate_exhausted_key(&self) -> SecretVaultResult<()> {
+        let new_key = self.cipher.generate_data_encryption_key()?;
+        let mut keys = self.keys.write().await;
+        if keys.current_key_encryptions.load(Ordering::Relaxed) >= self.max_encryptions_per_key {
+            info!(
+                "Vault encryption key has reached the limit of {} encryptions and is being rotated",
+                self.max_encryptions_per_key
+            );
+            keys.rotate(new_key);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -36,11 +229,26 @@ impl SecretVaultEncryption for SecretVaultRingAeadEncryption {
         secret_vault_key: &SecretVaultKey,
         secret_value: &SecretValue,
     ) -> SecretVaultResult<EncryptedSecretValue> {
-        let encrypted = self
-            .ring_encryption
-            .encrypt_value(secret_vault_key.to_aad(), secret_value, &self.vault_secret)
-            .await?;
-        Ok(encrypted.into())
+        let aad = secret_vault_key.to_aad_with_mode(self.aad_mode);
+        loop {
+            {
+                let keys = self.keys.read().await;
+                if keys.current_key_encryptions.fetch_add(1, Ordering::Relaxed)
+                    < self.max_encryptions_per_key
+                {
+                    let encrypted = self
+                        .cipher
+                        .encrypt_value(&aad, secret_value, &keys.current_key)
+                        .await?;
+
+                    let mut encrypted_value = Vec::with_capacity(KEY_ID_LEN + encrypted.len());
+                    encrypted_value.extend_from_slice(&keys.current_key_id.to_be_bytes());
+                    encrypted_value.extend_from_slice(&encrypted);
+                    return Ok(EncryptedSecretValue(encrypted_value));
+                }
+            }
+            self.rotate_exhausted_key().await?;
+        }
     }
 
     async fn decrypt_value(
@@ -48,14 +256,69 @@ impl SecretVaultEncryption for SecretVaultRingAeadEncryption {
         secret_vault_key: &SecretVaultKey,
         encrypted_secret_value: &EncryptedSecretValue,
     ) -> SecretVaultResult<SecretValue> {
-        Ok(self
-            .ring_encryption
+        if encrypted_secret_value.value().len() < KEY_ID_LEN {
+            return Err(SecretVaultEncryptionError::create(
+                "ENCRYPTED_VALUE_FORMAT",
+                "Encrypted value is too short to contain a key id",
+            ));
+        }
+        let (key_id_bytes, cipher_text) = encrypted_secret_value.value().split_at(KEY_ID_LEN);
+        let key_id = u32::from_be_bytes(key_id_bytes.try_into().unwrap());
+
+        let keys = self.keys.read().await;
+        let key = if key_id == keys.current_key_id {
+            &keys.current_key
+        } else {
+            keys.previous_keys
+                .iter()
+                .find(|(previous_key_id, _)| *previous_key_id == key_id)
+                .map(|(_, previous_key)| previous_key)
+                .ok_or_else(|| {
+                    SecretVaultEncryptionError::create(
+                        "ENCRYPTION_KEY_NOT_FOUND",
+                        "Encryption key for the value has already been retired",
+                    )
+                })?
+        };
+
+        self.cipher
             .decrypt_value(
-                secret_vault_key.to_aad(),
-                &encrypted_secret_value.clone().into(),
-                &self.vault_secret,
+                &secret_vault_key.to_aad_with_mode(self.aad_mode),
+                cipher_text,
+                key,
             )
-            .await?)
+            .await
+    }
+
+    async fn rotate_key(&self) -> SecretVaultResult<bool> {
+        let new_key = self.cipher.generate_data_encryption_key()?;
+        self.keys.write().await.rotate(new_key);
+        Ok(true)
+    }
+
+    async fn retire_previous_keys(&self) -> SecretVaultResult<()> {
+        self.keys.write().await.previous_keys.clear();
+        Ok(())
+    }
+
+    async fn current_key_id(&self) -> Option<u32> {
+        Some(self.keys.read().await.current_key_id)
+    }
+
+    async fn retire_keys_before(&self, key_id: u32) -> SecretVaultResult<()> {
+        let mut keys = self.keys.write().await;
+        // Previous keys are kept in the order of rotations
+        let retired_keys = keys
+            .previous_keys
+            .iter()
+            .position(|(previous_key_id, _)| *previous_key_id == key_id)
+            .unwrap_or(keys.previous_keys.len());
+        keys.previous_keys.drain(..retired_keys);
+        Ok(())
+    }
+
+    async fn requires_reencryption(&self) -> bool {
+        !self.keys.read().await.previous_keys.is_empty()
     }
 }
 
@@ -67,7 +330,6 @@ mod tests {
     use proptest::prelude::*;
     use proptest::strategy::ValueTree;
     use proptest::test_runner::TestRunner;
-    use rvstruct::*;
 
     async fn encryption_test_for(mock_secret_value: SecretValue) {
         let mock_secret_name: SecretName = "test".to_string().into();
@@ -132,4 +394,254 @@ mod tests {
             .await
             .expect_err("Unable to decrypt data");
     }
+
+    async fn swapped_key_decrypt(
+        encryption: &SecretVaultRingAeadEncryption,
+        secret_vault_key1: &SecretVaultKey,
+        secret_vault_key2: &SecretVaultKey,
+    ) -> SecretVaultResult<SecretValue> {
+        let mock_secret_value = SecretValue::new("42".repeat(1024).as_bytes().to_vec());
+        let encrypted_value = encryption
+            .encrypt_value(secret_vault_key1, &mock_secret_value)
+            .await
+            .unwrap();
+        encryption
+            .decrypt_value(secret_vault_key2, &encrypted_value)
+            .await
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_namespace_test_attest() {
+        let mock_secret_vault_key1: SecretVaultKey =
+            SecretVaultKey::new("test".to_string().into()).with_namespace("ns1".to_string().into());
+        let mock_secret_vault_key2: SecretVaultKey =
+            SecretVaultKey::new("test".to_string().into()).with_namespace("ns2".to_string().into());
+        let mock_secret_vault_key3: SecretVaultKey = SecretVaultKey::new("test".to_string().into());
+
+        let encryption = SecretVaultRingAeadEncryption::new().unwrap();
+        swapped_key_decrypt(
+            &encryption,
+            &mock_secret_vault_key1,
+            &mock_secret_vault_key2,
+        )
+        .await
+        .expect_err("Unable to decrypt data");
+        swapped_key_decrypt(
+            &encryption,
+            &mock_secret_vault_key1,
+            &mock_secret_vault_key3,
+        )
+        .await
+        .expect_err("Unable to decrypt data");
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_version_test_attest() {
+        let mock_secret_vault_key1: SecretVaultKey = SecretVaultKey::new("test".to_string().into())
+            .with_secret_version("1".to_string().into());
+        let mock_secret_vault_key2: SecretVaultKey = SecretVaultKey::new("test".to_string().into())
+            .with_secret_version("2".to_string().into());
+
+        let encryption = SecretVaultRingAeadEncryption::new().unwrap();
+        swapped_key_decrypt(
+            &encryption,
+            &mock_secret_vault_key1,
+            &mock_secret_vault_key2,
+        )
+        .await
+        .expect_err("Unable to decrypt data");
+
+        // Ambiguous concatenations of components must produce different AAD
+        let mock_secret_vault_key3: SecretVaultKey =
+            SecretVaultKey::new("test1".to_string().into()).with_namespace("ns".to_string().into());
+        let mock_secret_vault_key4: SecretVaultKey =
+            SecretVaultKey::new("test".to_string().into()).with_namespace("1ns".to_string().into());
+        swapped_key_decrypt(
+            &encryption,
+            &mock_secret_vault_key3,
+            &mock_secret_vault_key4,
+        )
+        .await
+        .expect_err("Unable to decrypt data");
+    }
+
+    #[tokio::test]
+    async fn key_rotation_test() {
+        let mock_secret_vault_key: SecretVaultKey = SecretVaultKey::new("test".to_string().into());
+        let mock_secret_value = SecretValue::new("42".repeat(1024).as_bytes().to_vec());
+
+        let encryption = SecretVaultRingAeadEncryption::new().unwrap();
+        let encrypted_value1 = encryption
+            .encrypt_value(&mock_secret_vault_key, &mock_secret_value)
+            .await
+            .unwrap();
+
+        assert!(encryption.rotate_key().await.unwrap());
+
+        let encrypted_value2 = encryption
+            .encrypt_value(&mock_secret_vault_key, &mock_secret_value)
+            .await
+            .unwrap();
+        assert_ne!(encrypted_value1.value()[..4], encrypted_value2.value()[..4]);
+
+        for encrypted_value in [&encrypted_value1, &encrypted_value2] {
+            assert_eq!(
+                encryption
+                    .decrypt_value(&mock_secret_vault_key, encrypted_value)
+                    .await
+                    .unwrap(),
+                mock_secret_value
+            );
+        }
+
+        encryption.retire_previous_keys().await.unwrap();
+
+        encryption
+            .decrypt_value(&mock_secret_vault_key, &encrypted_value1)
+            .await
+            .expect_err("Unable to decrypt data with retired key");
+        assert_eq!(
+            encryption
+                .decrypt_value(&mock_secret_vault_key, &encrypted_value2)
+                .await
+                .unwrap(),
+            mock_secret_value
+        );
+    }
+
+    #[tokio::test]
+    async fn aead_algorithms_test() {
+        let mock_secret_vault_key: SecretVaultKey = SecretVaultKey::new("test".to_string().into());
+        let mock_secret_value = SecretValue::new("42".repeat(1024).as_bytes().to_vec());
+
+        for algorithm in [
+            SecretVaultAeadAlgorithm::Aes256Gcm,
+            SecretVaultAeadAlgorithm::ChaCha20Poly1305,
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadAlgorithm::XChaCha20Poly1305,
+            #[cfg(feature = "rustcrypto-aead-encryption")]
+            SecretVaultAeadAlgorithm::Aes256GcmSiv,
+        ] {
+            let encryption = SecretVaultRingAeadEncryption::with_aead_algorithm(algorithm).unwrap();
+            let encrypted_value = encryption
+                .encrypt_value(&mock_secret_vault_key, &mock_secret_value)
+                .await
+                .unwrap();
+            assert_eq!(
+                encryption
+                    .decrypt_value(&mock_secret_vault_key, &encrypted_value)
+                    .await
+                    .unwrap(),
+                mock_secret_value
+            );
+            swapped_key_decrypt(
+                &encryption,
+                &mock_secret_vault_key,
+                &SecretVaultKey::new("test2".to_string().into()),
+            )
+            .await
+            .expect_err("Unable to decrypt data");
+        }
+    }
+
+    #[test]
+    fn ring_algorithm_usage_limit_test() {
+        assert_eq!(
+            SecretVaultRingAeadEncryption::with_algorithm(&ring::aead::CHACHA20_POLY1305)
+                .unwrap()
+                .max_encryptions_per_key,
+            SecretVaultAeadAlgorithm::ChaCha20Poly1305.max_encryptions_per_key()
+        );
+        assert_eq!(
+            SecretVaultRingAeadEncryption::with_algorithm(&ring::aead::AES_128_GCM)
+                .unwrap()
+                .max_encryptions_per_key,
+            1 << 32
+        );
+    }
+
+    #[tokio::test]
+    async fn automatic_rekey_test() {
+        let mock_secret_vault_key: SecretVaultKey = SecretVaultKey::new("test".to_string().into());
+        let mock_secret_value = SecretValue::new("42".repeat(64).as_bytes().to_vec());
+
+        let encryption = SecretVaultRingAeadEncryption::new()
+            .unwrap()
+            .with_max_encryptions_per_key(2);
+
+        let mut encrypted_values = Vec::new();
+        for _ in 0..5 {
+            encrypted_values.push(
+                encryption
+                    .encrypt_value(&mock_secret_vault_key, &mock_secret_value)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let key_ids: Vec<&[u8]> = encrypted_values
+            .iter()
+            .map(|encrypted_value| &encrypted_value.value()[..4])
+            .collect();
+        assert_eq!(key_ids[0], key_ids[1]);
+        assert_ne!(key_ids[1], key_ids[2]);
+        assert_ne!(key_ids[3], key_ids[4]);
+
+        for encrypted_value in encrypted_values.iter() {
+            assert_eq!(
+                encryption
+                    .decrypt_value(&mock_secret_vault_key, encrypted_value)
+                    .await
+                    .unwrap(),
+                mock_secret_value
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn vault_key_rotation_test() {
+        let mut runner = TestRunner::default();
+        let mock_secrets_store = generate_mock_secrets_source("default".into())
+            .new_tree(&mut runner)
+            .unwrap()
+            .current();
+
+        let vault = crate::SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_encryption(SecretVaultRingAeadEncryption::new().unwrap())
+            .build()
+            .unwrap()
+            .with_secret_refs(mock_secrets_store.keys().iter().collect());
+
+        vault.refresh().await.unwrap();
+        vault.rotate_encryption_key().await.unwrap();
+
+        for secret_ref in mock_secrets_store.keys() {
+            assert_eq!(
+                crate::SecretVaultView::get_secret_by_ref(&vault, &secret_ref)
+                    .await
+                    .unwrap()
+                    .map(|secret| secret.value),
+                mock_secrets_store.get(&secret_ref)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn secret_name_only_aad_mode_test() {
+        let mock_secret_vault_key1: SecretVaultKey = SecretVaultKey::new("test".to_string().into())
+            .with_secret_version("1".to_string().into());
+        let mock_secret_vault_key2: SecretVaultKey = SecretVaultKey::new("test".to_string().into())
+            .with_secret_version("2".to_string().into());
+
+        let encryption = SecretVaultRingAeadEncryption::new()
+            .unwrap()
+            .with_aad_mode(SecretVaultAadMode::SecretNameOnly);
+        swapped_key_decrypt(
+            &encryption,
+            &mock_secret_vault_key1,
+            &mock_secret_vault_key2,
+        )
+        .await
+        .unwrap();
+    }
 }
@@ -0,0 +1,141 @@
+use crate::errors::*;
+use crate::ring_encryption::SecretVaultAeadAlgorithm;
+use crate::SecretVaultResult;
+use aes_gcm_siv::Aes256GcmSiv;
+use chacha20poly1305::aead::generic_array::typenum::Unsigned;
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use kms_aead::DataEncryptionKey;
+use ring::rand::{SecureRandom, SystemRandom};
+use rvstruct::ValueStruct;
+use secret_vault_value::SecretValue;
+
+const RUSTCRYPTO_KEY_LEN: usize = 32;
+
+pub(crate) fn generate_data_encryption_key(
+    secure_rand: &SystemRandom,
+) -> SecretVaultResult<DataEncryptionKey> {
+    let mut key_data = vec![0u8; RUSTCRYPTO_KEY_LEN];
+    secure_rand.fill(&mut key_data).map_err(|e| {
+        SecretVaultEncryptionError::create(
+            "ENCRYPTION_KEY",
+            &format!("Unable to generate key: {e}"),
+        )
+    })?;
+    Ok(DataEncryptionKey(SecretValue::new(key_data)))
+}
+
+pub(crate) fn encrypt_value(
+    algorithm: SecretVaultAeadAlgorithm,
+    secure_rand: &SystemRandom,
+    aad: &[u8],
+    plain_text: &SecretValue,
+    encryption_key: &DataEncryptionKey,
+) -> SecretVaultResult<Vec<u8>> {
+    match algorithm {
+        SecretVaultAeadAlgorithm::XChaCha20Poly1305 => {
+            aead_encrypt::<XChaCha20Poly1305>(secure_rand, aad, plain_text, encryption_key)
+        }
+        SecretVaultAeadAlgorithm::Aes256GcmSiv => {
+            aead_encrypt::<Aes256GcmSiv>(secure_rand, aad, plain_text, encryption_key)
+        }
+        _ => Err(unsupported_algorithm_error(algorithm)),
+    }
+}
+
+pub(crate) fn decrypt_value(
+    algorithm: SecretVaultAeadAlgorithm,
+    aad: &[u8],
+    cipher_text: &[u8],
+    encryption_key: &DataEncryptionKey,
+) -> SecretVaultResult<SecretValue> {
+    match algorithm {
+        SecretVaultAeadAlgorithm::XChaCha20Poly1305 => {
+            aead_decrypt::<XChaCha20Poly1305>(aad, cipher_text, encryption_key)
+        }
+        SecretVaultAeadAlgorithm::Aes256GcmSiv => {
+            aead_decrypt::<Aes256GcmSiv>(aad, cipher_text, encryption_key)
+        }
+        _ => Err(unsupported_algorithm_error(algorithm)),
+    }
+}
+
+fn aead_encrypt<C>(
+    secure_rand: &SystemRandom,
+    aad: &[u8],
+    plain_text: &SecretValue,
+    encryption_key: &DataEncryptionKey,
+) -> SecretVaultResult<Vec<u8>>
+where
+    C: Aead + AeadCore + KeyInit,
+{
+    let cipher = aead_cipher::<C>(encryption_key)?;
+
+    let mut nonce: GenericArray<u8, C::NonceSize> = GenericArray::default();
+    secure_rand.fill(nonce.as_mut_slice()).map_err(|e| {
+        SecretVaultEncryptionError::create(
+            "ENCRYPTION_NONCE",
+            &format!("Unable to generate nonce: {e}"),
+        )
+    })?;
+
+    let encrypted = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plain_text.as_sensitive_bytes(),
+                aad,
+            },
+        )
+        .map_err(|_| SecretVaultEncryptionError::create("ENCRYPTION", "Unable to encrypt value"))?;
+
+    Ok([nonce.as_slice(), encrypted.as_slice()].concat())
+}
+
+fn aead_decrypt<C>(
+    aad: &[u8],
+    cipher_text: &[u8],
+    encryption_key: &DataEncryptionKey,
+) -> SecretVaultResult<SecretValue>
+where
+    C: Aead + AeadCore + KeyInit,
+{
+    let nonce_len = C::NonceSize::to_usize();
+    if cipher_text.len() < nonce_len {
+        return Err(SecretVaultEncryptionError::create(
+            "ENCRYPTED_VALUE_FORMAT",
+            "Encrypted value is too short to contain a nonce",
+        ));
+    }
+    let (nonce, encrypted) = cipher_text.split_at(nonce_len);
+
+    let cipher = aead_cipher::<C>(encryption_key)?;
+    let plain_text = cipher
+        .decrypt(
+            GenericArray::from_slice(nonce),
+            Payload {
+                msg: encrypted,
+                aad,
+            },
+        )
+        .map_err(|_| SecretVaultEncryptionError::create("DECRYPTION", "Unable to decrypt value"))?;
+
+    Ok(SecretValue::new(plain_text))
+}
+
+fn aead_cipher<C>(encryption_key: &DataEncryptionKey) -> SecretVaultResult<C>
+where
+    C: KeyInit,
+{
+    C::new_from_slice(encryption_key.value().as_sensitive_bytes()).map_err(|_| {
+        SecretVaultEncryptionError::create("ENCRYPTION_KEY", "Invalid encryption key length")
+    })
+}
+
+fn unsupported_algorithm_error(algorithm: SecretVaultAeadAlgorithm) -> SecretVaultError {
+    SecretVaultEncryptionError::create(
+        "ENCRYPTION_ALGORITHM",
+        &format!("Algorithm {algorithm:?} isn't supported by the pure Rust backend"),
+    )
+}
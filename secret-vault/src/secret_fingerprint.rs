@@ -0,0 +1,122 @@
+#![cfg_attr(
+    not(feature = "ring-aead-encryption"),
+    allow(dead_code, unused_variables)
+)]
+
+use crate::SecretVaultResult;
+use secret_vault_value::SecretValue;
+
+#[cfg(feature = "ring-aead-encryption")]
+use crate::errors::*;
+#[cfg(feature = "ring-aead-encryption")]
+use ring::hmac;
+#[cfg(feature = "ring-aead-encryption")]
+use std::sync::OnceLock;
+
+/// HMAC-SHA256 of a secret value used to detect unchanged secrets without decrypting cached values.
+#[derive(Clone)]
+pub struct SecretFingerprint(Vec<u8>);
+
+impl std::fmt::Debug for SecretFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretFingerprint(***)")
+    }
+}
+
+/// Calculates fingerprints with a random key generated for each store,
+/// so fingerprints can't be checked against guessed values outside the process.
+/// The key is generated on the first use, so failing to generate it is reported as an error.
+/// Fingerprints are available with the `ring-aead-encryption` feature only,
+/// otherwise the store decrypts cached values to compare them.
+#[derive(Default)]
+pub struct SecretFingerprinter {
+    #[cfg(feature = "ring-aead-encryption")]
+    key: OnceLock<hmac::Key>,
+}
+
+impl SecretFingerprinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "ring-aead-encryption")]
+    fn key(&self) -> SecretVaultResult<&hmac::Key> {
+        if let Some(key) = self.key.get() {
+            return Ok(key);
+        }
+
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &ring::rand::SystemRandom::new())
+            .map_err(|_| {
+                SecretVaultSystemError::create(
+                    "FINGERPRINT_KEY",
+                    "Unable to generate a random fingerprint key",
+                )
+            })?;
+        // Another thread may have set the key in between, its key is used then
+        Ok(self.key.get_or_init(|| key))
+    }
+
+    /// Returns `None` without the `ring-aead-encryption` feature.
+    pub fn fingerprint(
+        &self,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<Option<SecretFingerprint>> {
+        #[cfg(feature = "ring-aead-encryption")]
+        {
+            let tag = hmac::sign(self.key()?, secret_value.as_sensitive_bytes());
+            Ok(Some(SecretFingerprint(tag.as_ref().to_vec())))
+        }
+        #[cfg(not(feature = "ring-aead-encryption"))]
+        Ok(None)
+    }
+
+    /// Checks in constant time if the value has the fingerprint.
+    pub fn matches(
+        &self,
+        fingerprint: &SecretFingerprint,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<bool> {
+        #[cfg(feature = "ring-aead-encryption")]
+        {
+            Ok(hmac::verify(
+                self.key()?,
+                secret_value.as_sensitive_bytes(),
+                &fingerprint.0,
+            )
+            .is_ok())
+        }
+        #[cfg(not(feature = "ring-aead-encryption"))]
+        Ok(false)
+    }
+}
+
+impl std::fmt::Debug for SecretFingerprinter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretFingerprinter")
+    }
+}
+
+#[cfg(all(test, feature = "ring-aead-encryption"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_test() {
+        let fingerprinter = SecretFingerprinter::new();
+        let test_fingerprint = fingerprinter
+            .fingerprint(&SecretValue::from("test-value"))
+            .unwrap()
+            .unwrap();
+
+        assert!(fingerprinter
+            .matches(&test_fingerprint, &SecretValue::from("test-value"))
+            .unwrap());
+        assert!(!fingerprinter
+            .matches(&test_fingerprint, &SecretValue::from("test-value2"))
+            .unwrap());
+        assert!(!SecretFingerprinter::new()
+            .matches(&test_fingerprint, &SecretValue::from("test-value"))
+            .unwrap());
+        assert_eq!(format!("{test_fingerprint:?}"), "SecretFingerprint(***)");
+    }
+}
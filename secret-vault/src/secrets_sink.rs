@@ -0,0 +1,73 @@
+use crate::{SecretMetadata, SecretVaultRef, SecretVaultResult, SecretVersion};
+use async_trait::*;
+use rsb_derive::*;
+use secret_vault_value::SecretValue;
+
+/// Version labels used while a new value of a secret is staged and promoted.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
+pub struct SecretVersionStages {
+    /// The label of the staged value while it is validated.
+    pub pending: SecretVersion,
+    /// The label the replaced current value is kept with (no previous value is kept if not specified).
+    pub previous: Option<SecretVersion>,
+}
+
+/// Optional write-back API for sources that are able to create and update secrets.
+#[async_trait]
+pub trait SecretsSink {
+    /// Creates a secret if it doesn't exist yet, or stores a new current value for it.
+    async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata>;
+
+    /// Adds a new version to an existing secret.
+    /// For sources with labelled versions (such as AWS staging labels) the version from
+    /// the reference is attached to the new version.
+    async fn add_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata>;
+
+    /// Disables the version specified in the reference, so it is not available to read anymore.
+    async fn disable_secret_version(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()>;
+
+    /// Deletes the secret with all of its versions.
+    async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()>;
+
+    /// Stages a new value of a secret with the pending label without changing its current value.
+    /// Returns the version to promote or discard later, or `None` for sources that can't stage
+    /// values without making them current. Those sources write the value only on promotion.
+    async fn stage_secret_version(
+        &self,
+        _secret_ref: &SecretVaultRef,
+        _secret_value: &SecretValue,
+        _stages: &SecretVersionStages,
+    ) -> SecretVaultResult<Option<SecretVersion>> {
+        Ok(None)
+    }
+
+    /// Makes the staged version the current value of a secret.
+    /// `secret_value` is the staged value and is written only if nothing has been staged.
+    async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        _staged_version: Option<&SecretVersion>,
+        _stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata> {
+        self.put_secret(secret_ref, secret_value).await
+    }
+
+    /// Discards the staged version (e.g. when it didn't pass validation).
+    async fn discard_secret_version(
+        &self,
+        _secret_ref: &SecretVaultRef,
+        _staged_version: Option<&SecretVersion>,
+        _stages: &SecretVersionStages,
+    ) -> SecretVaultResult<()> {
+        Ok(())
+    }
+}
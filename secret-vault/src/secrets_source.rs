@@ -10,4 +10,44 @@ pub trait SecretsSource {
         &self,
         references: &[SecretVaultRef],
     ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>>;
+
+    /// Reads secrets returning a result for each reference, so failures of some secrets
+    /// don't discard the others. `Ok(None)` means the secret doesn't exist.
+    /// The default implementation reads secrets one by one using `get_secrets`.
+    async fn get_secrets_partially(
+        &self,
+        references: &[SecretVaultRef],
+    ) -> HashMap<SecretVaultRef, SecretVaultResult<Option<Secret>>> {
+        let mut result_map: HashMap<SecretVaultRef, SecretVaultResult<Option<Secret>>> =
+            HashMap::with_capacity(references.len());
+        for secret_ref in references {
+            let result = self
+                .get_secrets(std::slice::from_ref(secret_ref))
+                .await
+                .map(|mut secrets| secrets.remove(secret_ref));
+            result_map.insert(secret_ref.clone(), result);
+        }
+        result_map
+    }
+}
+
+#[async_trait]
+impl SecretsSource for Box<dyn SecretsSource + Send + Sync> {
+    fn name(&self) -> String {
+        self.as_ref().name()
+    }
+
+    async fn get_secrets(
+        &self,
+        references: &[SecretVaultRef],
+    ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
+        self.as_ref().get_secrets(references).await
+    }
+
+    async fn get_secrets_partially(
+        &self,
+        references: &[SecretVaultRef],
+    ) -> HashMap<SecretVaultRef, SecretVaultResult<Option<Secret>>> {
+        self.as_ref().get_secrets_partially(references).await
+    }
 }
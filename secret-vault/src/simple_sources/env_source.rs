@@ -1,4 +1,5 @@
 use crate::errors::*;
+use crate::vault_tracing::*;
 use crate::*;
 use async_trait::*;
 use rvstruct::*;
@@ -28,6 +29,7 @@ impl SecretsSource for InsecureEnvSource {
         let mut result_map: HashMap<SecretVaultRef, Secret> = HashMap::new();
 
         for secret_ref in references {
+            let _span = source_secret_span("InsecureEnvSource", &secret_ref.key).entered();
             let env_secret_name: String = format!(
                 "{}{}",
                 secret_ref.key.secret_name.value(),
@@ -1,16 +1,28 @@
 use crate::errors::*;
+use crate::vault_tracing::*;
 use crate::*;
 use async_trait::*;
 use rsb_derive::*;
 use rvstruct::*;
 use secret_vault_value::SecretValue;
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::*;
 
+static TMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug, Clone, Eq, PartialEq, Builder)]
 pub struct FilesSourceOptions {
     pub root_path: Option<Box<Path>>,
+
+    #[default = "0o400"]
+    pub file_mode: u32,
+
+    /// Owner of written secret files (Unix only). The current user/group if not specified.
+    pub owner_uid: Option<u32>,
+    pub owner_gid: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -26,6 +38,85 @@ impl FilesSource {
     pub fn with_options(options: FilesSourceOptions) -> Self {
         Self { options }
     }
+
+    pub(crate) fn secret_file_name(&self, secret_ref: &SecretVaultRef) -> String {
+        format!(
+            "{}{}{}",
+            self.options
+                .root_path
+                .as_ref()
+                .and_then(|rp| rp.to_str())
+                .map(|path| format!("{path}/"))
+                .unwrap_or_default(),
+            secret_ref.key.secret_name.value(),
+            secret_ref
+                .key
+                .secret_version
+                .as_ref()
+                .map(|sv| { format!("_v{}", sv.value()) })
+                .unwrap_or_default()
+        )
+    }
+
+    pub(crate) fn write_secret_file(
+        &self,
+        secret_file_name: &str,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<()> {
+        let secret_file_path = Path::new(secret_file_name);
+        let tmp_file_path: PathBuf = secret_file_path.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            secret_file_path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .unwrap_or_default(),
+            std::process::id(),
+            TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        trace!("Writing a secret file to: {}", secret_file_name);
+
+        let write_result = {
+            let mut open_options = std::fs::OpenOptions::new();
+            open_options.write(true).create_new(true);
+
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut open_options, self.options.file_mode);
+
+            open_options.open(&tmp_file_path).and_then(|mut tmp_file| {
+                #[cfg(unix)]
+                if self.options.owner_uid.is_some() || self.options.owner_gid.is_some() {
+                    std::os::unix::fs::fchown(
+                        &tmp_file,
+                        self.options.owner_uid,
+                        self.options.owner_gid,
+                    )?;
+                }
+                tmp_file.write_all(secret_value.as_sensitive_bytes())?;
+                tmp_file.sync_all()
+            })
+        }
+        .and_then(|_| std::fs::rename(&tmp_file_path, secret_file_path));
+
+        write_result.map_err(|err| {
+            std::fs::remove_file(&tmp_file_path).ok();
+            files_source_error(
+                "SECRET_FILE_WRITE",
+                format!("Unable to write secret file `{secret_file_name}`: {err}"),
+                err,
+            )
+        })
+    }
+}
+
+fn files_source_error(code: &str, message: String, err: std::io::Error) -> SecretVaultError {
+    SecretVaultError::SecretsSourceError(
+        SecretsSourceError::new(
+            SecretVaultErrorPublicGenericDetails::new(code.into()),
+            message,
+        )
+        .with_root_cause(Box::new(err)),
+    )
 }
 
 #[async_trait]
@@ -41,22 +132,8 @@ impl SecretsSource for FilesSource {
         let mut result_map: HashMap<SecretVaultRef, Secret> = HashMap::new();
 
         for secret_ref in references {
-            let secret_file_name: String = format!(
-                "{}{}{}",
-                self.options
-                    .root_path
-                    .as_ref()
-                    .and_then(|rp| rp.to_str())
-                    .map(|path| format!("{path}/"))
-                    .unwrap_or_default(),
-                secret_ref.key.secret_name.value(),
-                secret_ref
-                    .key
-                    .secret_version
-                    .as_ref()
-                    .map(|sv| { format!("_v{}", sv.value()) })
-                    .unwrap_or_else(|| "".to_string())
-            );
+            let _span = source_secret_span("FilesSource", &secret_ref.key).entered();
+            let secret_file_name: String = self.secret_file_name(secret_ref);
 
             trace!("Loading a secret file from: {}", &secret_file_name);
             match std::fs::read(Path::new(secret_file_name.as_str())) {
@@ -87,3 +164,376 @@ impl SecretsSource for FilesSource {
         Ok(result_map)
     }
 }
+
+#[async_trait]
+impl SecretsSink for FilesSource {
+    async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        self.write_secret_file(&self.secret_file_name(secret_ref), secret_value)?;
+        Ok(SecretMetadata::create_from_ref(secret_ref))
+    }
+
+    async fn add_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        if secret_ref.key.secret_version.is_none() {
+            return Err(SecretVaultInvalidParametersError::create(
+                "secret_version",
+                "Secret version must be specified to add it to files source",
+            ));
+        }
+        self.put_secret(secret_ref, secret_value).await
+    }
+
+    async fn disable_secret_version(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        if secret_ref.key.secret_version.is_none() {
+            return Err(SecretVaultInvalidParametersError::create(
+                "secret_version",
+                "Secret version must be specified to disable it",
+            ));
+        }
+        let secret_file_name = self.secret_file_name(secret_ref);
+        std::fs::rename(&secret_file_name, format!("{secret_file_name}.disabled")).map_err(|err| {
+            files_source_error(
+                "SECRET_FILE_DISABLE",
+                format!("Unable to disable secret file `{secret_file_name}`: {err}"),
+                err,
+            )
+        })
+    }
+
+    /// Deletes the secret file and all version files (`<name>_v<version>`, including disabled ones).
+    /// Only numeric versions and upper-case stage labels (such as `AWSPENDING`) are recognized,
+    /// so files of other secrets sharing the prefix (e.g. `<name>_validator`) are kept.
+    async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        let secret_file_name =
+            self.secret_file_name(&SecretVaultRef::new(secret_ref.key.secret_name.clone()));
+        let secret_file_path = Path::new(&secret_file_name);
+        let secret_dir = secret_file_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let versions_prefix = format!("{}_v", secret_ref.key.secret_name.value());
+
+        let delete_error = |err: std::io::Error| {
+            files_source_error(
+                "SECRET_FILE_DELETE",
+                format!("Unable to delete secret file `{secret_file_name}`: {err}"),
+                err,
+            )
+        };
+
+        let mut secret_files: Vec<PathBuf> = std::fs::read_dir(secret_dir)
+            .map_err(delete_error)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|file_name| file_name.strip_prefix(&versions_prefix))
+                    .is_some_and(is_secret_version_file_suffix)
+            })
+            .map(|entry| entry.path())
+            .collect();
+        if secret_file_path.exists() {
+            secret_files.push(secret_file_path.to_path_buf());
+        }
+
+        if secret_files.is_empty() {
+            return Err(delete_error(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            )));
+        }
+
+        for secret_file in secret_files {
+            trace!("Deleting a secret file: {}", secret_file.display());
+            std::fs::remove_file(&secret_file).map_err(delete_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the value to the pending version file (`<name>_v<pending>`).
+    async fn stage_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<Option<SecretVersion>> {
+        let pending_ref = secret_ref
+            .clone()
+            .with_secret_version(stages.pending.clone());
+        self.add_secret_version(&pending_ref, secret_value).await?;
+        Ok(Some(stages.pending.clone()))
+    }
+
+    /// Copies the current file to the previous version file and atomically renames
+    /// the staged file to the current one.
+    async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let secret_file_name = self.secret_file_name(secret_ref);
+
+        if let Some(previous_version) = &stages.previous {
+            match std::fs::read(&secret_file_name) {
+                Ok(current_value) => self.write_secret_file(
+                    &self.secret_file_name(
+                        &secret_ref
+                            .clone()
+                            .with_secret_version(previous_version.clone()),
+                    ),
+                    &SecretValue::new(current_value),
+                )?,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(files_source_error(
+                        "SECRET_FILE_PROMOTE",
+                        format!("Unable to read current secret file `{secret_file_name}`: {err}"),
+                        err,
+                    ))
+                }
+            }
+        }
+
+        match staged_version {
+            Some(staged_version) => {
+                let staged_file_name = self.secret_file_name(
+                    &secret_ref
+                        .clone()
+                        .with_secret_version(staged_version.clone()),
+                );
+                trace!(
+                    "Promoting a secret file {} to {}",
+                    staged_file_name,
+                    secret_file_name
+                );
+                std::fs::rename(&staged_file_name, &secret_file_name).map_err(|err| {
+                    files_source_error(
+                        "SECRET_FILE_PROMOTE",
+                        format!("Unable to promote secret file `{staged_file_name}`: {err}"),
+                        err,
+                    )
+                })?;
+            }
+            None => self.write_secret_file(&secret_file_name, secret_value)?,
+        }
+
+        Ok(SecretMetadata::create_from_ref(secret_ref))
+    }
+
+    /// Removes the staged version file.
+    async fn discard_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        staged_version: Option<&SecretVersion>,
+        _stages: &SecretVersionStages,
+    ) -> SecretVaultResult<()> {
+        if let Some(staged_version) = staged_version {
+            let staged_file_name = self.secret_file_name(
+                &secret_ref
+                    .clone()
+                    .with_secret_version(staged_version.clone()),
+            );
+            std::fs::remove_file(&staged_file_name).map_err(|err| {
+                files_source_error(
+                    "SECRET_FILE_DISCARD",
+                    format!("Unable to discard staged secret file `{staged_file_name}`: {err}"),
+                    err,
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn is_secret_version_file_suffix(suffix: &str) -> bool {
+    let version = suffix.strip_suffix(".disabled").unwrap_or(suffix);
+    !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use secret_vault_value::SecretValue;
+
+    #[tokio::test]
+    async fn write_secret_files_test() {
+        let mock_secret_dir = tempfile::tempdir().unwrap();
+        let files_source = FilesSource::with_options(
+            FilesSourceOptions::new().with_root_path(mock_secret_dir.path().into()),
+        );
+
+        let secret_ref = SecretVaultRef::new("test-secret".into());
+        let secret_ref_v2 = secret_ref.clone().with_secret_version("2".into());
+
+        files_source
+            .put_secret(&secret_ref, &SecretValue::from("test-value1"))
+            .await
+            .unwrap();
+        files_source
+            .put_secret(&secret_ref, &SecretValue::from("test-value2"))
+            .await
+            .unwrap();
+        files_source
+            .add_secret_version(&secret_ref_v2, &SecretValue::from("test-value3"))
+            .await
+            .unwrap();
+
+        let secrets = files_source
+            .get_secrets(&[secret_ref.clone(), secret_ref_v2.clone()])
+            .await
+            .unwrap();
+        assert_eq!(
+            secrets.get(&secret_ref).map(|secret| &secret.value),
+            Some(&SecretValue::from("test-value2"))
+        );
+        assert_eq!(
+            secrets.get(&secret_ref_v2).map(|secret| &secret.value),
+            Some(&SecretValue::from("test-value3"))
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let file_mode = std::fs::metadata(mock_secret_dir.path().join("test-secret"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(file_mode & 0o777, 0o400);
+        }
+
+        files_source
+            .disable_secret_version(&secret_ref_v2)
+            .await
+            .unwrap();
+        files_source
+            .add_secret_version(
+                &secret_ref.clone().with_secret_version("3".into()),
+                &SecretValue::from("test-value4"),
+            )
+            .await
+            .unwrap();
+        files_source.delete_secret(&secret_ref).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_dir(mock_secret_dir.path()).unwrap().count(),
+            0
+        );
+        assert!(files_source.delete_secret(&secret_ref).await.is_err());
+
+        let secrets = files_source
+            .get_secrets(&[
+                secret_ref.with_required(false),
+                secret_ref_v2.with_required(false),
+            ])
+            .await
+            .unwrap();
+        assert!(secrets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn delete_secret_keeps_sibling_files_test() {
+        let mock_secret_dir = tempfile::tempdir().unwrap();
+        let files_source = FilesSource::with_options(
+            FilesSourceOptions::new().with_root_path(mock_secret_dir.path().into()),
+        );
+
+        let secret_ref = SecretVaultRef::new("db".into());
+        for secret_ref in [
+            secret_ref.clone(),
+            secret_ref.clone().with_secret_version("1".into()),
+            secret_ref.clone().with_secret_version("AWSPREVIOUS".into()),
+            SecretVaultRef::new("db_validator".into()),
+            SecretVaultRef::new("db_vault_token".into()),
+            SecretVaultRef::new("db_vsomething".into()),
+        ] {
+            files_source
+                .put_secret(&secret_ref, &SecretValue::from("test-value"))
+                .await
+                .unwrap();
+        }
+        files_source
+            .disable_secret_version(&secret_ref.clone().with_secret_version("1".into()))
+            .await
+            .unwrap();
+
+        files_source.delete_secret(&secret_ref).await.unwrap();
+
+        let mut remaining_files: Vec<String> = std::fs::read_dir(mock_secret_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining_files.sort();
+        assert_eq!(
+            remaining_files,
+            vec!["db_validator", "db_vault_token", "db_vsomething"]
+        );
+    }
+
+    #[tokio::test]
+    async fn stage_and_promote_secret_files_test() {
+        let mock_secret_dir = tempfile::tempdir().unwrap();
+        let files_source = FilesSource::with_options(
+            FilesSourceOptions::new().with_root_path(mock_secret_dir.path().into()),
+        );
+        let secret_ref = SecretVaultRef::new("test-secret".into());
+        let stages = SecretVersionStages::new("pending".into()).with_previous("previous".into());
+        let read_file =
+            |file_name: &str| std::fs::read_to_string(mock_secret_dir.path().join(file_name)).ok();
+
+        files_source
+            .put_secret(&secret_ref, &SecretValue::from("test-value1"))
+            .await
+            .unwrap();
+
+        let staged_version = files_source
+            .stage_secret_version(&secret_ref, &SecretValue::from("test-value2"), &stages)
+            .await
+            .unwrap();
+        assert_eq!(staged_version, Some("pending".into()));
+        assert_eq!(read_file("test-secret").as_deref(), Some("test-value1"));
+        assert_eq!(
+            read_file("test-secret_vpending").as_deref(),
+            Some("test-value2")
+        );
+
+        files_source
+            .promote_secret_version(
+                &secret_ref,
+                &SecretValue::from("test-value2"),
+                staged_version.as_ref(),
+                &stages,
+            )
+            .await
+            .unwrap();
+        assert_eq!(read_file("test-secret").as_deref(), Some("test-value2"));
+        assert_eq!(
+            read_file("test-secret_vprevious").as_deref(),
+            Some("test-value1")
+        );
+        assert_eq!(read_file("test-secret_vpending"), None);
+
+        let staged_version = files_source
+            .stage_secret_version(&secret_ref, &SecretValue::from("test-value3"), &stages)
+            .await
+            .unwrap();
+        files_source
+            .discard_secret_version(&secret_ref, staged_version.as_ref(), &stages)
+            .await
+            .unwrap();
+        assert_eq!(read_file("test-secret").as_deref(), Some("test-value2"));
+        assert_eq!(read_file("test-secret_vpending"), None);
+    }
+}
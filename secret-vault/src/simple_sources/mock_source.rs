@@ -78,6 +78,153 @@ impl SecretsSource for MockSecretsSource {
     }
 }
 
+#[async_trait]
+impl SecretsSink for MockSecretsSource {
+    async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let mut secrets = self.secrets.lock().unwrap();
+        secrets.retain(|existing_ref, _| existing_ref.key != secret_ref.key);
+        secrets.insert(secret_ref.clone(), secret_value.clone());
+        Ok(SecretMetadata::create_from_ref(secret_ref))
+    }
+
+    async fn add_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let mut secrets = self.secrets.lock().unwrap();
+        if !secrets.keys().any(|existing_ref| {
+            existing_ref.key.secret_name == secret_ref.key.secret_name
+                && existing_ref.key.namespace == secret_ref.key.namespace
+        }) {
+            return Err(SecretVaultError::DataNotFoundError(
+                SecretVaultDataNotFoundError::new(
+                    SecretVaultErrorPublicGenericDetails::new("MOCK_SECRET_NOT_FOUND".into()),
+                    format!(
+                        "Unable to add a version to a secret that doesn't exist {:?}.",
+                        secret_ref.key.secret_name
+                    ),
+                ),
+            ));
+        }
+        secrets.retain(|existing_ref, _| existing_ref.key != secret_ref.key);
+        secrets.insert(secret_ref.clone(), secret_value.clone());
+        Ok(SecretMetadata::create_from_ref(secret_ref))
+    }
+
+    async fn disable_secret_version(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        if secret_ref.key.secret_version.is_none() {
+            return Err(SecretVaultInvalidParametersError::create(
+                "secret_version",
+                "Secret version must be specified to disable it",
+            ));
+        }
+        self.secrets
+            .lock()
+            .unwrap()
+            .retain(|existing_ref, _| existing_ref.key != secret_ref.key);
+        Ok(())
+    }
+
+    async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+        self.secrets.lock().unwrap().retain(|existing_ref, _| {
+            existing_ref.key.secret_name != secret_ref.key.secret_name
+                || existing_ref.key.namespace != secret_ref.key.namespace
+        });
+        Ok(())
+    }
+
+    async fn stage_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<Option<SecretVersion>> {
+        let pending_ref = secret_ref
+            .clone()
+            .with_secret_version(stages.pending.clone());
+        self.add_secret_version(&pending_ref, secret_value).await?;
+        Ok(Some(stages.pending.clone()))
+    }
+
+    async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: &SecretValue,
+        staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let mut secrets = self.secrets.lock().unwrap();
+        let current_key = &secret_ref.key;
+
+        let staged_value = match staged_version {
+            Some(staged_version) => {
+                let staged_key = current_key
+                    .clone()
+                    .with_secret_version(staged_version.clone());
+                let staged_ref = secrets
+                    .keys()
+                    .find(|existing_ref| existing_ref.key == staged_key)
+                    .cloned();
+                staged_ref
+                    .and_then(|staged_ref| secrets.remove(&staged_ref))
+                    .ok_or_else(|| {
+                        SecretVaultError::DataNotFoundError(SecretVaultDataNotFoundError::new(
+                            SecretVaultErrorPublicGenericDetails::new(
+                                "MOCK_SECRET_NOT_FOUND".into(),
+                            ),
+                            format!("Staged secret version is not found {staged_key:?}."),
+                        ))
+                    })?
+            }
+            None => secret_value.clone(),
+        };
+
+        let current = secrets
+            .iter()
+            .find(|(existing_ref, _)| existing_ref.key == *current_key)
+            .map(|(existing_ref, value)| (existing_ref.clone(), value.clone()));
+
+        if let Some((current_ref, current_value)) = current {
+            secrets.remove(&current_ref);
+            if let Some(previous_version) = &stages.previous {
+                let previous_key = current_key
+                    .clone()
+                    .with_secret_version(previous_version.clone());
+                secrets.retain(|existing_ref, _| existing_ref.key != previous_key);
+                secrets.insert(
+                    current_ref.with_secret_version(previous_version.clone()),
+                    current_value,
+                );
+            }
+        }
+
+        secrets.insert(secret_ref.clone(), staged_value);
+        Ok(SecretMetadata::create_from_ref(secret_ref))
+    }
+
+    async fn discard_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        staged_version: Option<&SecretVersion>,
+        _stages: &SecretVersionStages,
+    ) -> SecretVaultResult<()> {
+        if let Some(staged_version) = staged_version {
+            self.disable_secret_version(
+                &secret_ref
+                    .clone()
+                    .with_secret_version(staged_version.clone()),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub mod source_tests {
     use crate::*;
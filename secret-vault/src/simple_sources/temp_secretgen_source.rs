@@ -1,11 +1,25 @@
 use crate::errors::*;
 use crate::*;
 use async_trait::*;
-use ring::rand::{SecureRandom, SystemRandom};
+use ring::rand::SecureRandom;
 use rsb_derive::*;
 use secret_vault_value::SecretValue;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::*;
+use zeroize::Zeroizing;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TempSecretGeneratorKind {
+    RandomBytes,
+    Password(PasswordPolicy),
+    Token(TokenAlphabet),
+    UuidV4,
+    Ed25519KeyPair,
+    EcdsaP256KeyPair,
+    HmacKey(HmacKeyAlgorithm),
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Builder)]
 pub struct TempSecretOptions {
@@ -16,6 +30,41 @@ pub struct TempSecretOptions {
 
     #[default = "true"]
     pub printable: bool,
+
+    #[default = "TempSecretGeneratorKind::RandomBytes"]
+    pub kind: TempSecretGeneratorKind,
+
+    #[default = "0"]
+    pub keep_previous_versions: usize,
+
+    pub regenerate_interval: Option<Duration>,
+}
+
+impl TempSecretOptions {
+    fn secret_generator(&self) -> SecretVaultResult<Box<dyn SecretGenerator + Send + Sync>> {
+        Ok(match &self.kind {
+            TempSecretGeneratorKind::RandomBytes => Box::new(RandomBytesSecretGenerator::new(
+                self.key_len,
+                self.printable,
+            )),
+            TempSecretGeneratorKind::Password(policy) => {
+                Box::new(PasswordSecretGenerator::new(policy.clone())?)
+            }
+            TempSecretGeneratorKind::Token(alphabet) => {
+                Box::new(TokenSecretGenerator::new(self.key_len, *alphabet))
+            }
+            TempSecretGeneratorKind::UuidV4 => Box::new(UuidV4SecretGenerator::new()),
+            TempSecretGeneratorKind::Ed25519KeyPair => {
+                Box::new(Ed25519KeyPairSecretGenerator::new())
+            }
+            TempSecretGeneratorKind::EcdsaP256KeyPair => {
+                Box::new(EcdsaP256KeyPairSecretGenerator::new())
+            }
+            TempSecretGeneratorKind::HmacKey(algorithm) => {
+                Box::new(HmacKeySecretGenerator::new(*algorithm))
+            }
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -40,38 +89,131 @@ impl TempSecretGenSourceOptions {
     }
 }
 
-#[derive(Debug)]
+struct TempSecretGenerations {
+    last_version: u64,
+    generated_at: Instant,
+    secrets: VecDeque<(SecretVersion, Secret)>,
+}
+
 pub struct TempSecretGenSource {
     options: TempSecretGenSourceOptions,
-    secure_rand: SystemRandom,
-    generated_secrets: HashMap<SecretVaultKey, SecretValue>,
+    generators: HashMap<SecretVaultKey, Box<dyn SecretGenerator + Send + Sync>>,
+    generations: Mutex<HashMap<SecretVaultKey, TempSecretGenerations>>,
 }
 
 impl TempSecretGenSource {
     pub fn with_options(options: TempSecretGenSourceOptions) -> SecretVaultResult<Self> {
-        let secure_rand = SystemRandom::new();
+        let mut generators = HashMap::new();
 
-        let secrets_to_pregen: Vec<(&SecretVaultKey, &TempSecretOptions)> = options
-            .registered_secrets
-            .iter()
-            .filter(|(_, options)| !options.regenerate_on_refresh)
-            .collect();
+        for (key, secret_options) in options.registered_secrets.iter() {
+            generators.insert(key.clone(), secret_options.secret_generator()?);
+        }
 
-        let mut generated_secrets = HashMap::new();
+        let source = Self {
+            options,
+            generators,
+            generations: Mutex::new(HashMap::new()),
+        };
 
-        for (key, options) in secrets_to_pregen {
-            debug!("Pre-generating a new secret value for {:?}", key);
-            generated_secrets.insert(
-                key.clone(),
-                generate_secret_value(&secure_rand, options.key_len, options.printable)?,
-            );
+        {
+            let mut generations = source.generations.lock().unwrap();
+            for (key, secret_options) in source.options.registered_secrets.iter() {
+                if !secret_options.regenerate_on_refresh {
+                    debug!("Pre-generating a new secret value for {:?}", key);
+                    source.generate_next_version(key, &mut generations)?;
+                }
+            }
         }
 
-        Ok(Self {
-            options,
-            secure_rand,
-            generated_secrets,
-        })
+        Ok(source)
+    }
+
+    /// Generates a new version of the registered secret keeping the configured number of previous versions.
+    pub fn regenerate_secret(&self, key: &SecretVaultKey) -> SecretVaultResult<SecretVersion> {
+        let mut generations = self.generations.lock().unwrap();
+        self.generate_next_version(key, &mut generations)
+    }
+
+    fn generate_next_version(
+        &self,
+        key: &SecretVaultKey,
+        generations: &mut HashMap<SecretVaultKey, TempSecretGenerations>,
+    ) -> SecretVaultResult<SecretVersion> {
+        let (secret_options, generator) = match (
+            self.options.registered_secrets.get(key),
+            self.generators.get(key),
+        ) {
+            (Some(secret_options), Some(generator)) => (secret_options, generator),
+            _ => return Err(temp_secret_not_found_error(key)),
+        };
+
+        let secret_ref = SecretVaultRef::new(key.secret_name.clone())
+            .opt_secret_version(key.secret_version.clone())
+            .opt_namespace(key.namespace.clone());
+        let secret = generator.generate_secret(&secret_ref)?;
+
+        let key_generations =
+            generations
+                .entry(key.clone())
+                .or_insert_with(|| TempSecretGenerations {
+                    last_version: 0,
+                    generated_at: Instant::now(),
+                    secrets: VecDeque::new(),
+                });
+
+        key_generations.last_version += 1;
+        key_generations.generated_at = Instant::now();
+
+        let version: SecretVersion = key_generations.last_version.to_string().into();
+        key_generations
+            .secrets
+            .push_front((version.clone(), secret));
+        key_generations
+            .secrets
+            .truncate(secret_options.keep_previous_versions + 1);
+
+        debug!("Generated a new secret version {:?} for {:?}", version, key);
+
+        Ok(version)
+    }
+
+    fn requires_new_version(
+        &self,
+        key: &SecretVaultKey,
+        generations: &HashMap<SecretVaultKey, TempSecretGenerations>,
+    ) -> bool {
+        match (
+            self.options.registered_secrets.get(key),
+            generations.get(key),
+        ) {
+            (Some(_), None) => true,
+            (Some(secret_options), Some(key_generations)) => {
+                secret_options.regenerate_on_refresh
+                    || secret_options
+                        .regenerate_interval
+                        .iter()
+                        .any(|interval| key_generations.generated_at.elapsed() >= *interval)
+            }
+            (None, _) => false,
+        }
+    }
+}
+
+fn temp_secret_not_found_error(key: &SecretVaultKey) -> SecretVaultError {
+    SecretVaultError::DataNotFoundError(SecretVaultDataNotFoundError::new(
+        SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
+        format!(
+            "Secret is required but not found in registered secrets {:?}",
+            key
+        ),
+    ))
+}
+
+impl std::fmt::Debug for TempSecretGenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TempSecretGenSource")
+            .field("options", &self.options)
+            .finish()
     }
 }
 
@@ -80,23 +222,23 @@ pub fn generate_secret_value(
     key_len: usize,
     printable: bool,
 ) -> SecretVaultResult<SecretValue> {
-    let effective_key_len = if printable { key_len / 2 } else { key_len };
+    // Printable values are hex encoded, so every random byte produces two characters
+    let effective_key_len = if printable {
+        key_len.div_ceil(2)
+    } else {
+        key_len
+    };
 
     let mut rand_key_data: Vec<u8> = vec![0; effective_key_len];
-    secure_rand.fill(&mut rand_key_data).map_err(|e| {
-        SecretVaultError::SecretsSourceError(
-            SecretsSourceError::new(
-                SecretVaultErrorPublicGenericDetails::new(format!(
-                    "Unable to initialise random key: {e:?}"
-                )),
-                format!("Unable to initialise random key: {e}"),
-            )
-            .with_root_cause(Box::new(e)),
-        )
-    })?;
+    secure_rand
+        .fill(&mut rand_key_data)
+        .map_err(random_generator_error)?;
 
     if printable {
-        Ok(SecretValue::from(hex::encode(rand_key_data)))
+        let rand_key_data = Zeroizing::new(rand_key_data);
+        let mut printable_key = Zeroizing::new(hex::encode(rand_key_data.as_slice()));
+        printable_key.truncate(key_len);
+        Ok(SecretValue::from(&printable_key))
     } else {
         Ok(SecretValue::from(rand_key_data))
     }
@@ -114,36 +256,51 @@ impl SecretsSource for TempSecretGenSource {
     ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
         let mut result_map: HashMap<SecretVaultRef, Secret> = HashMap::new();
 
+        let mut generations = self.generations.lock().unwrap();
+
         for secret_ref in references {
-            match self.options.registered_secrets.get(&secret_ref.key) {
-                Some(secret_options) => {
-                    let secret_value = match self.generated_secrets.get(&secret_ref.key) {
-                        Some(secret_value) => secret_value.clone(),
-                        None => {
-                            debug!("Generating a new secret value for {:?}", secret_ref.key);
-                            generate_secret_value(
-                                &self.secure_rand,
-                                secret_options.key_len,
-                                secret_options.printable,
-                            )?
-                        }
-                    };
+            let registered_secret = if self.generators.contains_key(&secret_ref.key) {
+                Some((secret_ref.key.clone(), None))
+            } else {
+                let unversioned_key = secret_ref.key.clone().opt_secret_version(None);
+                secret_ref
+                    .key
+                    .secret_version
+                    .as_ref()
+                    .filter(|_| self.generators.contains_key(&unversioned_key))
+                    .map(|secret_version| (unversioned_key.clone(), Some(secret_version)))
+            };
 
+            let generated_secret = match registered_secret {
+                Some((key, requested_version)) => {
+                    if requested_version.is_none() && self.requires_new_version(&key, &generations)
+                    {
+                        debug!("Generating a new secret value for {:?}", key);
+                        self.generate_next_version(&key, &mut generations)?;
+                    }
+                    generations.get(&key).and_then(|key_generations| {
+                        key_generations.secrets.iter().find(|(version, _)| {
+                            requested_version
+                                .iter()
+                                .all(|requested| *requested == version)
+                        })
+                    })
+                }
+                None => None,
+            };
+
+            match generated_secret {
+                Some((version, secret)) => {
+                    let mut metadata = SecretMetadata::create_from_ref(secret_ref);
+                    metadata.annotations = secret.metadata.annotations.clone();
+                    metadata.version(version.clone());
                     result_map.insert(
                         secret_ref.clone(),
-                        Secret::new(secret_value, SecretMetadata::create_from_ref(secret_ref)),
+                        Secret::new(secret.value.clone(), metadata),
                     );
                 }
                 None if secret_ref.required => {
-                    return Err(SecretVaultError::DataNotFoundError(
-                        SecretVaultDataNotFoundError::new(
-                            SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
-                            format!(
-                                "Secret is required but not found in registered secrets {:?}",
-                                &secret_ref.key
-                            ),
-                        ),
-                    ))
+                    return Err(temp_secret_not_found_error(&secret_ref.key));
                 }
                 None => {
                     debug!("Secret or secret version {:?} doesn't exist and since it is not required it is skipped",secret_ref.key);
@@ -154,3 +311,116 @@ impl SecretsSource for TempSecretGenSource {
         Ok(result_map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn printable_secret_value_len_test() {
+        let secure_rand = ring::rand::SystemRandom::new();
+        for key_len in [15, 16, 32] {
+            let printable_value = generate_secret_value(&secure_rand, key_len, true).unwrap();
+            let printable_bytes = printable_value.as_sensitive_bytes();
+            assert_eq!(printable_bytes.len(), key_len);
+            assert!(printable_bytes.iter().all(|c| c.is_ascii_hexdigit()));
+
+            let value = generate_secret_value(&secure_rand, key_len, false).unwrap();
+            assert_eq!(value.as_sensitive_bytes().len(), key_len);
+        }
+    }
+
+    #[tokio::test]
+    async fn generator_kinds_test() {
+        let password_ref = SecretVaultRef::new("test-password".into());
+        let key_pair_ref = SecretVaultRef::new("test-key-pair".into());
+
+        let source = TempSecretGenSource::with_options(
+            TempSecretGenSourceOptions::new()
+                .add_secret_generator(
+                    &password_ref.key,
+                    TempSecretOptions::new(0).with_kind(TempSecretGeneratorKind::Password(
+                        PasswordPolicy::new(20).with_symbols(true),
+                    )),
+                )
+                .add_secret_generator(
+                    &key_pair_ref.key,
+                    TempSecretOptions::new(0).with_kind(TempSecretGeneratorKind::Ed25519KeyPair),
+                ),
+        )
+        .unwrap();
+
+        let secrets = source
+            .get_secrets(&[password_ref.clone(), key_pair_ref.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(secrets[&password_ref].value.as_sensitive_bytes().len(), 20);
+        assert!(secrets[&key_pair_ref]
+            .metadata
+            .annotations
+            .iter()
+            .flatten()
+            .any(|annotation| annotation.name == PUBLIC_KEY_ANNOTATION));
+
+        assert!(TempSecretGenSource::with_options(
+            TempSecretGenSourceOptions::new().add_secret_generator(
+                &password_ref.key,
+                TempSecretOptions::new(0).with_kind(TempSecretGeneratorKind::Password(
+                    PasswordPolicy::new(20)
+                        .with_lowercase(false)
+                        .with_uppercase(false)
+                        .with_digits(false),
+                )),
+            ),
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn regenerate_versions_test() {
+        let secret_ref = SecretVaultRef::new("test-regenerated".into());
+
+        let source = TempSecretGenSource::with_options(
+            TempSecretGenSourceOptions::new().add_secret_generator(
+                &secret_ref.key,
+                TempSecretOptions::new(32)
+                    .with_regenerate_on_refresh(true)
+                    .with_keep_previous_versions(1),
+            ),
+        )
+        .unwrap();
+
+        let first_secret = source
+            .get_secrets(std::slice::from_ref(&secret_ref))
+            .await
+            .unwrap()[&secret_ref]
+            .clone();
+        let second_secret = source
+            .get_secrets(std::slice::from_ref(&secret_ref))
+            .await
+            .unwrap()[&secret_ref]
+            .clone();
+        assert_eq!(first_secret.metadata.version, Some("1".into()));
+        assert_eq!(second_secret.metadata.version, Some("2".into()));
+        assert_ne!(first_secret.value, second_secret.value);
+
+        let first_version_ref = secret_ref.clone().with_secret_version("1".into());
+        assert_eq!(
+            source
+                .get_secrets(std::slice::from_ref(&first_version_ref))
+                .await
+                .unwrap()[&first_version_ref]
+                .value,
+            first_secret.value
+        );
+
+        source.regenerate_secret(&secret_ref.key).unwrap();
+
+        assert!(source
+            .get_secrets(&[first_version_ref.with_required(false)])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::*;
+use arc_swap::ArcSwap;
+use secret_vault_value::SecretValue;
+use std::sync::Arc;
+
+/// A snapshot handle republished atomically by the vault after each successful refresh.
+/// Reading doesn't require locks or an async runtime, and clones share the same snapshot.
+#[derive(Clone)]
+pub struct SecretVaultAutoSnapshot {
+    current: Arc<ArcSwap<SecretVaultHashMapSnapshot>>,
+}
+
+impl SecretVaultAutoSnapshot {
+    pub(crate) fn new() -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(
+                SecretVaultHashMapSnapshot::with_secrets(Vec::new()),
+            )),
+        }
+    }
+
+    pub(crate) fn publish(&self, secrets: Vec<Secret>) {
+        self.current
+            .store(Arc::new(SecretVaultHashMapSnapshot::with_secrets(secrets)));
+    }
+
+    /// Returns the current snapshot, which isn't affected by further refreshes.
+    pub fn load(&self) -> Arc<SecretVaultHashMapSnapshot> {
+        self.current.load_full()
+    }
+}
+
+impl SecretVaultSnapshot for SecretVaultAutoSnapshot {
+    fn get_secret_by_ref(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Secret>> {
+        self.current.load().get_secret_by_ref(secret_ref)
+    }
+
+    fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        self.current.load().with_secret_by_ref(secret_ref, f)
+    }
+
+    fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>> {
+        self.current.load().get_secret_as_by_ref(secret_ref)
+    }
+}
@@ -0,0 +1,311 @@
+use crate::errors::*;
+use crate::*;
+use ring::aead::{Aad, Algorithm, LessSafeKey, Nonce, UnboundKey, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use secret_vault_value::SecretValue;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::*;
+
+#[cfg(not(feature = "ahash"))]
+type SecretVaultEncryptedSnapshotMap =
+    std::collections::HashMap<SecretVaultKey, SecretVaultEncryptedSnapshotValue>;
+
+#[cfg(feature = "ahash")]
+type SecretVaultEncryptedSnapshotMap =
+    ahash::AHashMap<SecretVaultKey, SecretVaultEncryptedSnapshotValue>;
+
+struct SecretVaultEncryptedSnapshotValue {
+    /// `None` if the secret couldn't be encrypted when the snapshot was built.
+    data: Option<Vec<u8>>,
+    metadata: SecretMetadata,
+}
+
+/// A snapshot keeping secret values encrypted with a local AEAD key
+/// and decrypting them synchronously on access.
+pub struct SecretVaultEncryptedSnapshot {
+    key: LessSafeKey,
+    secrets_map: SecretVaultEncryptedSnapshotMap,
+}
+
+impl SecretVaultEncryptedSnapshot {
+    fn decrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        stored_value: &SecretVaultEncryptedSnapshotValue,
+    ) -> SecretVaultResult<SecretValue> {
+        let data = stored_value.data.as_ref().ok_or_else(|| {
+            SecretVaultEncryptionError::create(
+                "SNAPSHOT_ENCRYPT",
+                "The secret couldn't be encrypted when the snapshot was built",
+            )
+        })?;
+        if data.len() < NONCE_LEN + self.key.algorithm().tag_len() {
+            return Err(SecretVaultEncryptionError::create(
+                "SNAPSHOT_DECRYPT",
+                "Encrypted secret in the snapshot is too short",
+            ));
+        }
+        let (nonce, cipher_text) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| {
+            SecretVaultEncryptionError::create(
+                "SNAPSHOT_DECRYPT",
+                "Invalid nonce in the encrypted snapshot",
+            )
+        })?;
+
+        let mut secret_value = SecretValue::new(cipher_text.to_vec());
+        let plain_text_len = self
+            .key
+            .open_in_place(
+                nonce,
+                Aad::from(secret_vault_key.to_canonical_aad()),
+                secret_value.ref_sensitive_value_mut(),
+            )
+            .map_err(|_| {
+                SecretVaultEncryptionError::create(
+                    "SNAPSHOT_DECRYPT",
+                    "Unable to decrypt a secret from the encrypted snapshot",
+                )
+            })?
+            .len();
+        secret_value
+            .ref_sensitive_value_mut()
+            .truncate(plain_text_len);
+
+        Ok(secret_value)
+    }
+}
+
+impl SecretVaultSnapshot for SecretVaultEncryptedSnapshot {
+    fn get_secret_by_ref(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Secret>> {
+        self.with_secret_by_ref(secret_ref, |secret_value, metadata| {
+            Secret::new(secret_value.clone(), metadata.clone())
+        })
+    }
+
+    fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        match self.secrets_map.get(&secret_ref.key) {
+            Some(stored_value) => {
+                let secret_value = self.decrypt_value(&secret_ref.key, stored_value)?;
+                Ok(Some(f(&secret_value, &stored_value.metadata)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Builds encrypted snapshots with a local key generated when the builder is created,
+/// separate from the vault encryption key. Nonces come from a counter shared by all snapshots
+/// built by the builder, so they are never reused for the key.
+pub struct SecretVaultEncryptedSnapshotBuilder {
+    key: LessSafeKey,
+    nonce_counter: AtomicU64,
+}
+
+impl SecretVaultEncryptedSnapshotBuilder {
+    pub fn new() -> SecretVaultResult<Self> {
+        Self::with_algorithm(&ring::aead::AES_256_GCM)
+    }
+
+    pub fn with_algorithm(algorithm: &'static Algorithm) -> SecretVaultResult<Self> {
+        let mut key_bytes = SecretValue::new(vec![0; algorithm.key_len()]);
+        SystemRandom::new()
+            .fill(key_bytes.ref_sensitive_value_mut())
+            .map_err(|_| {
+                SecretVaultEncryptionError::create(
+                    "SNAPSHOT_KEY",
+                    "Unable to generate a snapshot encryption key",
+                )
+            })?;
+
+        let key = UnboundKey::new(algorithm, key_bytes.ref_sensitive_value()).map_err(|_| {
+            SecretVaultEncryptionError::create(
+                "SNAPSHOT_KEY",
+                "Unable to create a snapshot encryption key",
+            )
+        })?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            nonce_counter: AtomicU64::new(0),
+        })
+    }
+
+    fn encrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        secret_value: &SecretValue,
+    ) -> Option<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[NONCE_LEN - 8..].copy_from_slice(
+            &self
+                .nonce_counter
+                .fetch_add(1, Ordering::Relaxed)
+                .to_be_bytes(),
+        );
+
+        let mut data = Vec::with_capacity(
+            NONCE_LEN + secret_value.ref_sensitive_value().len() + self.key.algorithm().tag_len(),
+        );
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(secret_value.ref_sensitive_value());
+
+        let tag = self
+            .key
+            .seal_in_place_separate_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(secret_vault_key.to_canonical_aad()),
+                &mut data[NONCE_LEN..],
+            )
+            .ok()?;
+        data.extend_from_slice(tag.as_ref());
+
+        Some(data)
+    }
+}
+
+impl SecretVaultSnapshotBuilder<SecretVaultEncryptedSnapshot>
+    for SecretVaultEncryptedSnapshotBuilder
+{
+    fn build_snapshot(&self, secrets: Vec<Secret>) -> SecretVaultEncryptedSnapshot {
+        let secrets_map: SecretVaultEncryptedSnapshotMap = secrets
+            .into_iter()
+            .map(|secret| {
+                let data = self.encrypt_value(&secret.metadata.key, &secret.value);
+                if data.is_none() {
+                    error!(
+                        "Unable to encrypt secret {:?} for snapshot",
+                        secret.metadata.key
+                    );
+                }
+                (
+                    secret.metadata.key.clone(),
+                    SecretVaultEncryptedSnapshotValue {
+                        data,
+                        metadata: secret.metadata,
+                    },
+                )
+            })
+            .collect();
+
+        SecretVaultEncryptedSnapshot {
+            key: self.key.clone(),
+            secrets_map,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_snapshot_test() {
+        let secrets: Vec<Secret> = ["secret1", "secret2"]
+            .into_iter()
+            .map(|secret_name| {
+                Secret::new(
+                    SecretValue::from(format!("{secret_name}-value")),
+                    SecretMetadata::create_from_ref(&SecretVaultRef::new(secret_name.into())),
+                )
+            })
+            .collect();
+
+        let snapshot = SecretVaultEncryptedSnapshotBuilder::new()
+            .unwrap()
+            .build_snapshot(secrets.clone());
+
+        for secret in secrets.iter() {
+            let stored_value = snapshot.secrets_map.get(&secret.metadata.key).unwrap();
+            assert!(!stored_value
+                .data
+                .as_ref()
+                .unwrap()
+                .windows(secret.value.ref_sensitive_value().len())
+                .any(|window| window == secret.value.ref_sensitive_value().as_slice()));
+
+            assert_eq!(
+                snapshot
+                    .get_secret(&secret.metadata.key.secret_name)
+                    .unwrap()
+                    .map(|secret| secret.value),
+                Some(secret.value.clone())
+            );
+        }
+
+        assert!(snapshot.get_secret(&"unknown".into()).unwrap().is_none());
+    }
+
+    #[test]
+    fn swapped_encrypted_value_test() {
+        let secret_refs = [
+            SecretVaultRef::new("secret1".into()),
+            SecretVaultRef::new("secret2".into()),
+        ];
+        let mut snapshot = SecretVaultEncryptedSnapshotBuilder::new()
+            .unwrap()
+            .build_snapshot(
+                secret_refs
+                    .iter()
+                    .map(|secret_ref| {
+                        Secret::new(
+                            SecretValue::from("test-value"),
+                            SecretMetadata::create_from_ref(secret_ref),
+                        )
+                    })
+                    .collect(),
+            );
+
+        let swapped_data = snapshot.secrets_map[&secret_refs[1].key].data.clone();
+        snapshot
+            .secrets_map
+            .get_mut(&secret_refs[0].key)
+            .unwrap()
+            .data = swapped_data;
+
+        assert!(snapshot.get_secret_by_ref(&secret_refs[0]).is_err());
+    }
+
+    #[test]
+    fn invalid_encrypted_value_test() {
+        let secret_refs = [
+            SecretVaultRef::new("secret1".into()),
+            SecretVaultRef::new("secret2".into()),
+        ];
+        let mut snapshot = SecretVaultEncryptedSnapshotBuilder::new()
+            .unwrap()
+            .build_snapshot(
+                secret_refs
+                    .iter()
+                    .map(|secret_ref| {
+                        Secret::new(
+                            SecretValue::from("test-value"),
+                            SecretMetadata::create_from_ref(secret_ref),
+                        )
+                    })
+                    .collect(),
+            );
+
+        snapshot
+            .secrets_map
+            .get_mut(&secret_refs[0].key)
+            .unwrap()
+            .data = Some(vec![0; NONCE_LEN - 1]);
+        snapshot
+            .secrets_map
+            .get_mut(&secret_refs[1].key)
+            .unwrap()
+            .data = None;
+
+        for secret_ref in secret_refs.iter() {
+            assert!(snapshot.get_secret_by_ref(secret_ref).is_err());
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use crate::secret_decode::SecretDecodedCache;
 use crate::*;
+use secret_vault_value::SecretValue;
+use std::sync::Arc;
 
 #[cfg(not(feature = "ahash"))]
 type SecretVaultSnapshotMap = std::collections::HashMap<SecretVaultKey, Secret>;
@@ -8,6 +11,7 @@ type SecretVaultSnapshotMap = ahash::AHashMap<SecretVaultKey, Secret>;
 
 pub struct SecretVaultHashMapSnapshot {
     secrets_map: SecretVaultSnapshotMap,
+    decoded_cache: SecretDecodedCache,
 }
 
 impl SecretVaultHashMapSnapshot {
@@ -17,7 +21,10 @@ impl SecretVaultHashMapSnapshot {
             .map(|secret| (secret.metadata.key.clone(), secret))
             .collect();
 
-        Self { secrets_map }
+        Self {
+            secrets_map,
+            decoded_cache: SecretDecodedCache::default(),
+        }
     }
 }
 
@@ -39,4 +46,31 @@ impl SecretVaultSnapshot for SecretVaultHashMapSnapshot {
     fn get_secret_by_ref(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Secret>> {
         Ok(self.secrets_map.get(&secret_ref.key).cloned())
     }
+
+    fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        Ok(self
+            .secrets_map
+            .get(&secret_ref.key)
+            .map(|secret| f(&secret.value, &secret.metadata)))
+    }
+
+    fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>> {
+        self.secrets_map
+            .get(&secret_ref.key)
+            .map(|secret| {
+                self.decoded_cache
+                    .get_or_decode::<T>(&secret_ref.key, &secret.value)
+            })
+            .transpose()
+    }
 }
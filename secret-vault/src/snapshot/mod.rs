@@ -1,2 +1,10 @@
 mod hash_map_snapshot;
 pub use hash_map_snapshot::*;
+
+mod auto_snapshot;
+pub use auto_snapshot::*;
+
+#[cfg(feature = "ring-aead-encryption")]
+mod encrypted_snapshot;
+#[cfg(feature = "ring-aead-encryption")]
+pub use encrypted_snapshot::*;
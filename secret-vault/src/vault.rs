@@ -1,9 +1,15 @@
 use crate::encryption::SecretVaultEncryption;
+use crate::errors::*;
 use crate::secrets_source::SecretsSource;
+use crate::vault_metrics::*;
 use crate::vault_store::SecretVaultStore;
+use crate::vault_tracing::*;
 use crate::*;
 use async_trait::async_trait;
-use std::sync::Arc;
+use secret_vault_value::SecretValue;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 use tracing::*;
 
 pub struct SecretVault<S, E>
@@ -14,6 +20,8 @@ where
     source: S,
     store: Arc<SecretVaultStore<E>>,
     refs: Vec<SecretVaultRef>,
+    auto_snapshot: OnceLock<SecretVaultAutoSnapshot>,
+    changes: tokio::sync::watch::Sender<u64>,
 }
 
 impl<S, E> SecretVault<S, E>
@@ -22,10 +30,16 @@ where
     E: SecretVaultEncryption + Sync + Send,
 {
     pub fn new(source: S, encrypter: E) -> SecretVaultResult<Self> {
+        Self::with_store(source, SecretVaultStore::new(encrypter))
+    }
+
+    pub(crate) fn with_store(source: S, store: SecretVaultStore<E>) -> SecretVaultResult<Self> {
         Ok(Self {
             source,
-            store: Arc::new(SecretVaultStore::new(encrypter)),
+            store: Arc::new(store),
             refs: Vec::new(),
+            auto_snapshot: OnceLock::new(),
+            changes: tokio::sync::watch::Sender::new(0),
         })
     }
 
@@ -55,6 +69,14 @@ where
     }
 
     pub async fn refresh(&self) -> SecretVaultResult<&Self> {
+        traced(
+            refresh_span(&self.source.name(), "all", self.refs.len()),
+            self.refresh_all(),
+        )
+        .await
+    }
+
+    async fn refresh_all(&self) -> SecretVaultResult<&Self> {
         info!(
             "Refreshing secrets from the source: {}. Expected: {}. Required: {}",
             self.source.name(),
@@ -65,7 +87,7 @@ where
                 .count()
         );
 
-        let mut secrets_map = self.source.get_secrets(&self.refs).await?;
+        let mut secrets_map = self.get_source_secrets(&self.refs).await?;
 
         for (secret_ref, secret) in secrets_map.drain() {
             self.store.insert(secret_ref, &secret).await?;
@@ -73,7 +95,10 @@ where
 
         info!("Secret vault contains: {} secrets", self.store.len().await);
 
-        self.compact().await?;
+        self.compact_secrets().await?;
+        self.store
+            .audit_mutation(SecretVaultAuditEventKind::Refresh, None, None);
+        self.secrets_updated().await?;
 
         Ok(self)
     }
@@ -89,6 +114,21 @@ where
             .cloned()
             .collect();
 
+        traced(
+            refresh_span(
+                &self.source.name(),
+                "auto_refresh",
+                refs_auto_refresh_enabled.len(),
+            ),
+            self.refresh_refs(&refs_auto_refresh_enabled),
+        )
+        .await
+    }
+
+    async fn refresh_refs(
+        &self,
+        refs_auto_refresh_enabled: &[SecretVaultRef],
+    ) -> SecretVaultResult<&Self> {
         trace!(
             "Refreshing secrets from the source: {}. All registered secrets: {}. Expected to be refreshed: {}",
             self.source.name(),
@@ -96,7 +136,7 @@ where
             refs_auto_refresh_enabled.len()
         );
 
-        let mut secrets_map = self.source.get_secrets(&refs_auto_refresh_enabled).await?;
+        let mut secrets_map = self.get_source_secrets(refs_auto_refresh_enabled).await?;
 
         for (secret_ref, secret) in secrets_map.drain() {
             self.store.insert(secret_ref, &secret).await?;
@@ -107,10 +147,22 @@ where
             self.store.len().await
         );
 
+        self.store
+            .audit_mutation(SecretVaultAuditEventKind::Refresh, None, None);
+        self.secrets_updated().await?;
+
         Ok(self)
     }
 
     pub async fn refresh_only_not_present(&self) -> SecretVaultResult<&Self> {
+        traced(
+            refresh_span(&self.source.name(), "not_present", self.refs.len()),
+            self.refresh_not_present(),
+        )
+        .await
+    }
+
+    async fn refresh_not_present(&self) -> SecretVaultResult<&Self> {
         let (existing_refs, missing_refs) = self.store.exists(&self.refs).await;
 
         if !missing_refs.is_empty() {
@@ -122,7 +174,7 @@ where
 
             let missing_refs: Vec<SecretVaultRef> = missing_refs.into_iter().cloned().collect();
 
-            let mut secrets_map = self.source.get_secrets(&missing_refs).await?;
+            let mut secrets_map = self.get_source_secrets(&missing_refs).await?;
 
             for (secret_ref, secret) in secrets_map.drain() {
                 self.store.insert(secret_ref, &secret).await?;
@@ -139,28 +191,126 @@ where
             );
         }
 
-        self.compact().await?;
+        self.compact_secrets().await?;
+        self.store
+            .audit_mutation(SecretVaultAuditEventKind::Refresh, None, None);
+        self.secrets_updated().await?;
 
         Ok(self)
     }
 
+    async fn get_source_secrets(
+        &self,
+        secret_refs: &[SecretVaultRef],
+    ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
+        let source_name = self.source.name();
+        let span = source_span(&source_name, secret_refs.len());
+        let started_at = Instant::now();
+        let result = traced(span.clone(), self.source.get_secrets(secret_refs)).await;
+        record_refresh(&source_name, started_at.elapsed(), refresh_outcome(&result));
+        if let Ok(secrets) = &result {
+            span.record("found", secrets.len());
+        }
+        result
+    }
+
+    pub(crate) fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Removes cached secrets of unregistered refs and notifies subscribers of changes.
     pub async fn compact(&self) -> SecretVaultResult<()> {
-        self.store.compact(&self.refs).await
+        self.compact_secrets().await?;
+        self.secrets_updated().await
+    }
+
+    async fn compact_secrets(&self) -> SecretVaultResult<()> {
+        self.store.compact(&self.refs).await?;
+        record_secrets_cached(self.store.len().await);
+        Ok(())
+    }
+
+    /// Rotates the encryption key and re-encrypts all cached secrets with the new key.
+    pub async fn rotate_encryption_key(&self) -> SecretVaultResult<()> {
+        if self.store.rotate_encryption_key().await? {
+            info!(
+                "Secret vault encryption key has been rotated for {} secrets",
+                self.store.len().await
+            );
+        } else {
+            debug!("Secret vault encryption doesn't support key rotation");
+        }
+        Ok(())
     }
 
     pub async fn store_len(&self) -> usize {
         self.store.len().await
     }
 
+    /// When the secret has been read from the source last time,
+    /// including refreshes returning the same value.
+    pub async fn get_secret_last_checked_at_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.store.get_last_checked_at(&secret_ref.key).await
+    }
+
     pub fn viewer(&self) -> SecretVaultViewer<E> {
         SecretVaultViewer::new(self.store.clone())
     }
 
+    /// Returns a viewer limited to the secrets allowed by the policy.
+    pub fn viewer_for(&self, policy: SecretVaultAccessPolicy) -> SecretVaultViewer<E> {
+        self.viewer().with_policy(policy)
+    }
+
     pub async fn snapshot<SNB, SN>(&self, builder: SNB) -> SecretVaultResult<SN>
     where
         SN: SecretVaultSnapshot,
         SNB: SecretVaultSnapshotBuilder<SN>,
     {
+        Ok(builder.build_snapshot(self.snapshot_secrets().await?))
+    }
+
+    /// Returns a snapshot handle which is republished after each successful refresh.
+    pub async fn auto_snapshot(&self) -> SecretVaultResult<SecretVaultAutoSnapshot> {
+        let auto_snapshot = self
+            .auto_snapshot
+            .get_or_init(SecretVaultAutoSnapshot::new)
+            .clone();
+        self.publish_auto_snapshot().await?;
+        Ok(auto_snapshot)
+    }
+
+    /// Subscribes to vault changes. The value changes every time cached secrets are changed
+    /// by refreshes or writes. Refreshes returning the same values don't signal changes.
+    pub fn subscribe_changes(&self) -> tokio::sync::watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    async fn secrets_updated(&self) -> SecretVaultResult<()> {
+        record_secrets_cached(self.store.len().await);
+
+        let revision = self.store.revision();
+        if *self.changes.borrow() != revision {
+            self.publish_auto_snapshot().await?;
+            self.changes.send_replace(revision);
+        } else {
+            trace!("Secret vault hasn't changed");
+        }
+        Ok(())
+    }
+
+    async fn publish_auto_snapshot(&self) -> SecretVaultResult<()> {
+        if let Some(auto_snapshot) = self.auto_snapshot.get() {
+            auto_snapshot.publish(self.snapshot_secrets().await?);
+            trace!("Secret vault auto snapshot has been republished");
+        }
+        Ok(())
+    }
+
+    async fn snapshot_secrets(&self) -> SecretVaultResult<Vec<Secret>> {
         let refs_allowed_in_snapshot: Vec<SecretVaultRef> = self
             .refs
             .iter()
@@ -176,7 +326,192 @@ where
             }
         }
 
-        Ok(builder.build_snapshot(secrets))
+        Ok(secrets)
+    }
+}
+
+impl<S, E> SecretVault<S, E>
+where
+    S: SecretsSource + Sync,
+    E: SecretVaultEncryption + Sync + Send,
+{
+    /// Refreshes all secrets like `refresh`, but failures of some secrets don't abort the refresh:
+    /// successfully read secrets are stored, and failed secrets keep their cached values.
+    pub async fn refresh_with_report(&self) -> SecretVaultResult<SecretVaultRefreshReport> {
+        traced(
+            refresh_span(&self.source.name(), "all", self.refs.len()),
+            async {
+                let report = self.store_source_secrets_partially(&self.refs).await;
+                self.compact_secrets().await?;
+                self.store
+                    .audit_mutation(SecretVaultAuditEventKind::Refresh, None, None);
+                self.secrets_updated().await?;
+                Ok(report)
+            },
+        )
+        .await
+    }
+
+    /// Refreshes secrets matching the predicate like `refresh_only`,
+    /// with the partial failures behaviour of `refresh_with_report`.
+    pub async fn refresh_only_with_report(
+        &self,
+        predicate: fn(&SecretVaultRef) -> bool,
+    ) -> SecretVaultResult<SecretVaultRefreshReport> {
+        let refs_auto_refresh_enabled: Vec<SecretVaultRef> = self
+            .refs
+            .iter()
+            .filter(|secret_ref| predicate(secret_ref))
+            .cloned()
+            .collect();
+
+        traced(
+            refresh_span(
+                &self.source.name(),
+                "auto_refresh",
+                refs_auto_refresh_enabled.len(),
+            ),
+            async {
+                let report = self
+                    .store_source_secrets_partially(&refs_auto_refresh_enabled)
+                    .await;
+                self.store
+                    .audit_mutation(SecretVaultAuditEventKind::Refresh, None, None);
+                self.secrets_updated().await?;
+                Ok(report)
+            },
+        )
+        .await
+    }
+
+    async fn store_source_secrets_partially(
+        &self,
+        secret_refs: &[SecretVaultRef],
+    ) -> SecretVaultRefreshReport {
+        let source_name = self.source.name();
+        let span = source_span(&source_name, secret_refs.len());
+        let started_at = Instant::now();
+        let mut results =
+            traced(span.clone(), self.source.get_secrets_partially(secret_refs)).await;
+        record_refresh(
+            &source_name,
+            started_at.elapsed(),
+            partial_refresh_outcome(&results),
+        );
+        span.record(
+            "found",
+            results
+                .values()
+                .filter(|result| matches!(result, Ok(Some(_))))
+                .count(),
+        );
+
+        let mut report = SecretVaultRefreshReport::new();
+        for secret_ref in secret_refs {
+            let outcome = match results.remove(secret_ref) {
+                Some(Ok(Some(secret))) => self
+                    .store_refreshed_secret(secret_ref, &secret)
+                    .await
+                    .unwrap_or_else(SecretVaultRefreshOutcome::Failed),
+                Some(Ok(None)) | Some(Err(SecretVaultError::DataNotFoundError(_))) | None => {
+                    SecretVaultRefreshOutcome::Missing
+                }
+                Some(Err(err)) => SecretVaultRefreshOutcome::Failed(err),
+            };
+            if let SecretVaultRefreshOutcome::Failed(err) = &outcome {
+                warn!("Unable to refresh secret {:?}: {}", secret_ref.key, err);
+            }
+            report.add_outcome(secret_ref.clone(), outcome);
+        }
+
+        info!(
+            "Secrets have been refreshed from the source: {}. Updated: {}. Unchanged: {}. Missing: {}. Failed: {}",
+            source_name,
+            report.updated().len(),
+            report.unchanged().len(),
+            report.missing().len(),
+            report.failed().len()
+        );
+
+        report
+    }
+
+    async fn store_refreshed_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret: &Secret,
+    ) -> SecretVaultResult<SecretVaultRefreshOutcome> {
+        if self.store.insert(secret_ref.clone(), secret).await? {
+            Ok(SecretVaultRefreshOutcome::Updated)
+        } else {
+            Ok(SecretVaultRefreshOutcome::Unchanged)
+        }
+    }
+}
+
+impl<S, E> SecretVault<S, E>
+where
+    S: SecretsSource + SecretsSink,
+    E: SecretVaultEncryption + Sync + Send,
+{
+    pub async fn put_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: SecretValue,
+    ) -> SecretVaultResult<SecretMetadata> {
+        info!(
+            "Writing a secret to the source: {}. Secret: {:?}",
+            self.source.name(),
+            secret_ref.key
+        );
+
+        let metadata = self.source.put_secret(secret_ref, &secret_value).await?;
+
+        self.store
+            .insert(
+                secret_ref.clone(),
+                &Secret::new(secret_value, metadata.clone()),
+            )
+            .await?;
+
+        self.secrets_updated().await?;
+
+        Ok(metadata)
+    }
+
+    /// Promotes a version staged with `SecretsSink::stage_secret_version` to the current
+    /// value of the secret in the source and caches it.
+    pub async fn promote_secret_version(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_value: SecretValue,
+        staged_version: Option<&SecretVersion>,
+        stages: &SecretVersionStages,
+    ) -> SecretVaultResult<SecretMetadata>
+    where
+        S: Sync,
+    {
+        info!(
+            "Promoting a staged secret version in the source: {}. Secret: {:?}",
+            self.source.name(),
+            secret_ref.key
+        );
+
+        let metadata = self
+            .source
+            .promote_secret_version(secret_ref, &secret_value, staged_version, stages)
+            .await?;
+
+        self.store
+            .insert(
+                secret_ref.clone(),
+                &Secret::new(secret_value, metadata.clone()),
+            )
+            .await?;
+
+        self.secrets_updated().await?;
+
+        Ok(metadata)
     }
 }
 
@@ -190,19 +525,75 @@ where
         &self,
         secret_ref: &SecretVaultRef,
     ) -> SecretVaultResult<Option<Secret>> {
-        self.store.get_secret(&secret_ref.key).await
+        let secret = self.store.get_secret(&secret_ref.key).await?;
+        self.store
+            .audit_access(&secret_ref.key, None, secret.is_some());
+        Ok(secret)
+    }
+
+    async fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R + Send,
+        R: Send,
+    {
+        let result = self.store.with_secret(&secret_ref.key, f).await?;
+        self.store
+            .audit_access(&secret_ref.key, None, result.is_some());
+        Ok(result)
+    }
+
+    async fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>> {
+        let decoded = self.store.get_secret_as(&secret_ref.key).await?;
+        self.store
+            .audit_access(&secret_ref.key, None, decoded.is_some());
+        Ok(decoded)
+    }
+
+    async fn get_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<Secret>> {
+        let secrets = self.store.get_secret_versions(&secret_ref.key).await?;
+        self.store
+            .audit_access(&secret_ref.key, None, !secrets.is_empty());
+        Ok(secrets)
+    }
+
+    async fn get_secret_metadata_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<SecretMetadata>> {
+        Ok(self.store.get_metadata(&secret_ref.key).await)
+    }
+
+    async fn list_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<SecretMetadata>> {
+        Ok(self.store.get_versions_metadata(&secret_ref.key).await)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::errors::*;
     use crate::source_tests::*;
     use crate::*;
+    use async_trait::async_trait;
     use chrono::Utc;
     use proptest::prelude::*;
     use proptest::strategy::ValueTree;
     use proptest::test_runner::TestRunner;
     use secret_vault_value::SecretValue;
+    use std::collections::HashMap;
+    use std::sync::Arc;
 
     #[tokio::test]
     async fn refresh_vault_test() {
@@ -278,4 +669,418 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn put_secret_test() {
+        let mut runner = TestRunner::default();
+        let mock_secrets_store = generate_mock_secrets_source("default".into())
+            .new_tree(&mut runner)
+            .unwrap()
+            .current();
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .build()
+            .unwrap()
+            .with_secret_refs(mock_secrets_store.keys().iter().collect());
+
+        vault.refresh().await.unwrap();
+
+        let secret_ref = mock_secrets_store.keys().first().cloned().unwrap();
+        let new_secret_value = SecretValue::new("new_secret_value".into());
+
+        vault
+            .put_secret(&secret_ref, new_secret_value.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref),
+            Some(new_secret_value.clone())
+        );
+        assert_eq!(
+            vault
+                .get_secret_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .map(|secret| secret.value),
+            Some(new_secret_value)
+        );
+    }
+
+    #[tokio::test]
+    async fn with_secret_test() {
+        let secret_refs: Vec<SecretVaultRef> = ["secret1", "secret2"]
+            .into_iter()
+            .map(|secret_name| {
+                SecretVaultRef::new(secret_name.into()).with_allow_in_snapshots(true)
+            })
+            .collect();
+        let mock_secrets_store = MockSecretsSource::new(
+            secret_refs
+                .iter()
+                .map(|secret_ref| {
+                    (
+                        secret_ref.clone(),
+                        SecretValue::from(format!("{}-value", secret_ref.key.secret_name)),
+                    )
+                })
+                .collect(),
+        );
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(secret_refs.iter().collect())
+            .build()
+            .unwrap();
+
+        vault.refresh().await.unwrap();
+
+        let viewer = vault.viewer();
+        let snapshot = vault
+            .snapshot(SecretVaultHashMapSnapshotBuilder::new())
+            .await
+            .unwrap();
+
+        for secret_ref in mock_secrets_store.keys() {
+            let expected = mock_secrets_store.get(&secret_ref);
+            let read_value = |value: &SecretValue, metadata: &SecretMetadata| {
+                assert_eq!(metadata.key, secret_ref.key);
+                value.clone()
+            };
+
+            assert_eq!(
+                vault
+                    .with_secret_by_ref(&secret_ref, read_value)
+                    .await
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(
+                viewer
+                    .with_secret_by_ref(&secret_ref, read_value)
+                    .await
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(
+                snapshot
+                    .with_secret_by_ref(&secret_ref, read_value)
+                    .unwrap(),
+                expected
+            );
+        }
+
+        let missing_ref = SecretVaultRef::new("missing-secret".into());
+        assert!(vault
+            .with_secret_by_ref(&missing_ref, |_, _| ())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(snapshot
+            .with_secret_by_ref(&missing_ref, |_, _| ())
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn auto_snapshot_test() {
+        let snapshot_secret_ref =
+            SecretVaultRef::new("snapshot-secret".into()).with_allow_in_snapshots(true);
+        let other_secret_ref = SecretVaultRef::new("other-secret".into());
+        let mut mock_secrets_store = MockSecretsSource::new(vec![
+            (snapshot_secret_ref.clone(), SecretValue::from("value1")),
+            (other_secret_ref.clone(), SecretValue::from("other-value")),
+        ]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&snapshot_secret_ref, &other_secret_ref])
+            .build()
+            .unwrap();
+
+        let auto_snapshot = vault.auto_snapshot().await.unwrap();
+        assert!(auto_snapshot
+            .get_secret_by_ref(&snapshot_secret_ref)
+            .unwrap()
+            .is_none());
+
+        vault.refresh().await.unwrap();
+        let previous_snapshot = auto_snapshot.load();
+
+        assert_eq!(
+            auto_snapshot
+                .get_secret_by_ref(&snapshot_secret_ref)
+                .unwrap()
+                .map(|secret| secret.value),
+            Some(SecretValue::from("value1"))
+        );
+        assert!(auto_snapshot
+            .get_secret_by_ref(&other_secret_ref)
+            .unwrap()
+            .is_none());
+
+        mock_secrets_store.add(snapshot_secret_ref.clone(), SecretValue::from("value2"));
+        vault
+            .refresh_only(|secret_ref| secret_ref.allow_in_snapshots)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            auto_snapshot
+                .clone()
+                .get_secret_by_ref(&snapshot_secret_ref)
+                .unwrap()
+                .map(|secret| secret.value),
+            Some(SecretValue::from("value2"))
+        );
+        assert_eq!(
+            previous_snapshot
+                .get_secret_by_ref(&snapshot_secret_ref)
+                .unwrap()
+                .map(|secret| secret.value),
+            Some(SecretValue::from("value1"))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_secret_as_test() {
+        let port_secret_ref = SecretVaultRef::new("port".into()).with_allow_in_snapshots(true);
+        let mut mock_secrets_store =
+            MockSecretsSource::new(vec![(port_secret_ref.clone(), SecretValue::from("8080"))]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&port_secret_ref])
+            .build()
+            .unwrap();
+
+        vault.refresh().await.unwrap();
+
+        let port = vault
+            .require_secret_as_by_ref::<u16>(&port_secret_ref)
+            .await
+            .unwrap();
+        assert_eq!(*port, 8080);
+        assert!(Arc::ptr_eq(
+            &port,
+            &vault
+                .viewer()
+                .require_secret_as_by_ref::<u16>(&port_secret_ref)
+                .await
+                .unwrap()
+        ));
+
+        let snapshot = vault.auto_snapshot().await.unwrap();
+        assert_eq!(
+            *snapshot
+                .require_secret_as_by_ref::<u16>(&port_secret_ref)
+                .unwrap(),
+            8080
+        );
+
+        mock_secrets_store.add(port_secret_ref.clone(), SecretValue::from("invalid-port"));
+        vault.refresh().await.unwrap();
+
+        assert!(matches!(
+            vault.get_secret_as_by_ref::<u16>(&port_secret_ref).await,
+            Err(SecretVaultError::DecodeError(err)) if err.secret_key == port_secret_ref.key
+        ));
+        assert!(matches!(
+            snapshot.get_secret_as_by_ref::<u16>(&port_secret_ref),
+            Err(SecretVaultError::DecodeError(_))
+        ));
+        assert_eq!(
+            vault
+                .get_secret_as::<String>(&port_secret_ref.key.secret_name)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&"invalid-port".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn retained_versions_test() {
+        let secret_ref = SecretVaultRef::new("test-versioned-secret".into());
+        let mut mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref.clone(), SecretValue::from("value1"))]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&secret_ref])
+            .with_retained_versions(2)
+            .build()
+            .unwrap();
+
+        vault.refresh().await.unwrap();
+        for value in ["value2", "value3", "value3", "value4"] {
+            mock_secrets_store.add(secret_ref.clone(), SecretValue::from(value));
+            vault.refresh().await.unwrap();
+        }
+
+        assert_eq!(
+            vault
+                .get_secret_versions_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|secret| secret.value)
+                .collect::<Vec<SecretValue>>(),
+            vec![
+                SecretValue::from("value4"),
+                SecretValue::from("value3"),
+                SecretValue::from("value2")
+            ]
+        );
+        assert_eq!(
+            vault
+                .viewer()
+                .list_secret_versions_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    struct FailingSecretsSource {
+        source: MockSecretsSource,
+        failing_secret_name: SecretName,
+    }
+
+    #[async_trait]
+    impl SecretsSource for FailingSecretsSource {
+        fn name(&self) -> String {
+            "FailingSecretsSource".to_string()
+        }
+
+        async fn get_secrets(
+            &self,
+            references: &[SecretVaultRef],
+        ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
+            if references
+                .iter()
+                .any(|secret_ref| secret_ref.key.secret_name == self.failing_secret_name)
+            {
+                return Err(SecretVaultError::SecretsSourceError(
+                    SecretsSourceError::new(
+                        SecretVaultErrorPublicGenericDetails::new("TEST_ERROR".into()),
+                        "Test source error".into(),
+                    ),
+                ));
+            }
+            self.source.get_secrets(references).await
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_with_report_test() {
+        let secret_ref1 = SecretVaultRef::new("secret1".into());
+        let failing_ref = SecretVaultRef::new("failing-secret".into());
+        let missing_ref = SecretVaultRef::new("missing-secret".into()).with_required(false);
+
+        let mut mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref1.clone(), SecretValue::from("test1"))]);
+
+        let vault = SecretVaultBuilder::with_source(FailingSecretsSource {
+            source: mock_secrets_store.clone(),
+            failing_secret_name: failing_ref.key.secret_name.clone(),
+        })
+        .with_secret_refs(vec![&secret_ref1, &failing_ref, &missing_ref])
+        .build()
+        .unwrap();
+
+        assert!(vault.refresh().await.is_err());
+        assert_eq!(vault.store_len().await, 0);
+
+        let report = vault.refresh_with_report().await.unwrap();
+        assert!(!report.is_complete());
+        assert_eq!(report.updated(), vec![&secret_ref1]);
+        assert_eq!(report.missing(), vec![&missing_ref]);
+        assert_eq!(report.failed().len(), 1);
+        assert_eq!(report.failed()[0].0, &failing_ref);
+        assert_eq!(
+            vault
+                .get_secret_by_ref(&secret_ref1)
+                .await
+                .unwrap()
+                .map(|secret| secret.value),
+            Some(SecretValue::from("test1"))
+        );
+
+        let report = vault.refresh_with_report().await.unwrap();
+        assert_eq!(report.unchanged(), vec![&secret_ref1]);
+
+        mock_secrets_store.add(secret_ref1.clone(), SecretValue::from("test1-updated"));
+        let report = vault.refresh_with_report().await.unwrap();
+        assert!(matches!(
+            report.outcome(&secret_ref1.key),
+            Some(SecretVaultRefreshOutcome::Updated)
+        ));
+    }
+
+    #[tokio::test]
+    async fn unchanged_secrets_test() {
+        let secret_ref = SecretVaultRef::new("secret1".into());
+        let mut mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref.clone(), SecretValue::from("test1"))]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&secret_ref])
+            .with_retained_versions(2)
+            .build()
+            .unwrap();
+        let mut changes = vault.subscribe_changes();
+
+        vault.refresh().await.unwrap();
+        assert!(changes.has_changed().unwrap());
+        changes.borrow_and_update();
+
+        let metadata = vault
+            .get_secret_by_ref(&secret_ref)
+            .await
+            .unwrap()
+            .unwrap()
+            .metadata;
+        let last_checked_at = vault
+            .get_secret_last_checked_at_by_ref(&secret_ref)
+            .await
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let report = vault.refresh_with_report().await.unwrap();
+        assert_eq!(report.unchanged(), vec![&secret_ref]);
+        assert!(!changes.has_changed().unwrap());
+
+        let unchanged_metadata = vault
+            .get_secret_by_ref(&secret_ref)
+            .await
+            .unwrap()
+            .unwrap()
+            .metadata;
+        assert_eq!(unchanged_metadata.cached_at, metadata.cached_at);
+        assert!(
+            vault
+                .get_secret_last_checked_at_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                > last_checked_at
+        );
+        assert_eq!(
+            vault
+                .get_secret_versions_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        mock_secrets_store.add(secret_ref.clone(), SecretValue::from("test2"));
+        vault.refresh().await.unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(
+            vault
+                .get_secret_versions_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
 }
@@ -0,0 +1,128 @@
+use crate::*;
+use rsb_derive::*;
+
+/// Limits secrets available through a viewer (see `SecretVault::viewer_for`).
+/// A secret is allowed if its name, its namespace or any of its labels is listed in the policy.
+/// Labels without values match labels with any value.
+#[derive(Debug, Clone, Eq, PartialEq, Builder)]
+pub struct SecretVaultAccessPolicy {
+    #[default = "Vec::new()"]
+    pub secret_names: Vec<SecretName>,
+    #[default = "Vec::new()"]
+    pub namespaces: Vec<SecretNamespace>,
+    #[default = "Vec::new()"]
+    pub labels: Vec<SecretMetadataLabel>,
+}
+
+impl SecretVaultAccessPolicy {
+    pub fn add_secret_name(mut self, secret_name: SecretName) -> Self {
+        self.secret_names.push(secret_name);
+        self
+    }
+
+    pub fn add_namespace(mut self, namespace: SecretNamespace) -> Self {
+        self.namespaces.push(namespace);
+        self
+    }
+
+    pub fn add_label(mut self, label: SecretMetadataLabel) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn allows_key(&self, secret_vault_key: &SecretVaultKey) -> bool {
+        self.secret_names.contains(&secret_vault_key.secret_name)
+            || secret_vault_key
+                .namespace
+                .as_ref()
+                .is_some_and(|namespace| self.namespaces.contains(namespace))
+    }
+
+    pub fn allows_metadata(&self, metadata: &SecretMetadata) -> bool {
+        self.allows_key(&metadata.key)
+            || metadata.labels.iter().flatten().any(|secret_label| {
+                self.labels.iter().any(|policy_label| {
+                    policy_label.name == secret_label.name
+                        && (policy_label.value.is_none()
+                            || policy_label.value == secret_label.value)
+                })
+            })
+    }
+
+    pub(crate) fn requires_metadata(&self) -> bool {
+        !self.labels.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::*;
+    use secret_vault_value::SecretValue;
+
+    #[tokio::test]
+    async fn viewer_for_policy_test() {
+        let billing_ref = SecretVaultRef::new("billing-api-key".into());
+        let aws_ref = SecretVaultRef::new("db-password".into()).with_namespace("aws".into());
+        let labeled_ref = SecretVaultRef::new("tls-cert".into()).add_predefined_label(
+            SecretMetadataLabel::new("owner".into()).with_value("billing".into()),
+        );
+        let denied_ref = SecretVaultRef::new("admin-password".into());
+
+        let vault = SecretVaultBuilder::with_source(MockSecretsSource::new(
+            [&billing_ref, &aws_ref, &labeled_ref, &denied_ref]
+                .into_iter()
+                .map(|secret_ref| (secret_ref.clone(), SecretValue::from("test-value")))
+                .collect(),
+        ))
+        .with_secret_refs(vec![&billing_ref, &aws_ref, &labeled_ref, &denied_ref])
+        .build()
+        .unwrap();
+        vault.refresh().await.unwrap();
+
+        let viewer = vault.viewer_for(
+            SecretVaultAccessPolicy::new()
+                .add_secret_name("billing-api-key".into())
+                .add_namespace("aws".into())
+                .add_label(SecretMetadataLabel::new("owner".into()).with_value("billing".into())),
+        );
+
+        for allowed_ref in [&billing_ref, &aws_ref, &labeled_ref] {
+            assert!(viewer
+                .get_secret_by_ref(allowed_ref)
+                .await
+                .unwrap()
+                .is_some());
+        }
+
+        for result in [
+            viewer.get_secret_by_ref(&denied_ref).await.map(|_| ()),
+            viewer
+                .with_secret_by_ref(&denied_ref, |_, _| ())
+                .await
+                .map(|_| ()),
+            viewer
+                .get_secret_as_by_ref::<String>(&denied_ref)
+                .await
+                .map(|_| ()),
+            viewer
+                .get_secret_versions_by_ref(&denied_ref)
+                .await
+                .map(|_| ()),
+        ] {
+            match result {
+                Err(SecretVaultError::AccessDeniedError(err)) => {
+                    assert_eq!(err.secret_key, denied_ref.key)
+                }
+                other => panic!("Unexpected result: {other:?}"),
+            }
+        }
+
+        assert!(vault
+            .viewer()
+            .get_secret_by_ref(&denied_ref)
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,339 @@
+use crate::*;
+use chrono::prelude::*;
+use rsb_derive::*;
+use rvstruct::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::*;
+
+/// Caller identity recorded in audit events, e.g. a module or a service name.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, ValueStruct)]
+pub struct SecretVaultPrincipal(String);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SecretVaultAuditEventKind {
+    Access,
+    AccessDenied,
+    Insert,
+    Compact,
+    Refresh,
+}
+
+impl SecretVaultAuditEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretVaultAuditEventKind::Access => "access",
+            SecretVaultAuditEventKind::AccessDenied => "access_denied",
+            SecretVaultAuditEventKind::Insert => "insert",
+            SecretVaultAuditEventKind::Compact => "compact",
+            SecretVaultAuditEventKind::Refresh => "refresh",
+        }
+    }
+}
+
+/// An audit event. Events never contain secret values.
+/// `key` is empty only for refresh events, and `hit` is specified only for access events.
+#[derive(Debug, Clone, Eq, PartialEq, Builder)]
+pub struct SecretVaultAuditEvent {
+    pub kind: SecretVaultAuditEventKind,
+    #[default = "Utc::now()"]
+    pub timestamp: DateTime<Utc>,
+    pub key: Option<SecretVaultKey>,
+    pub principal: Option<SecretVaultPrincipal>,
+    pub hit: Option<bool>,
+    pub version: Option<SecretVersion>,
+}
+
+/// Receives audit events. Sinks are called synchronously on every audited operation,
+/// so they shouldn't block for long.
+pub trait SecretVaultAuditSink: Send + Sync {
+    fn audit(&self, event: &SecretVaultAuditEvent);
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
+pub struct SecretVaultAuditOptions {
+    /// Records only every n-th successful secret read to reduce overhead on hot paths.
+    /// Misses and mutations are always recorded.
+    #[default = "1"]
+    pub access_hits_sample_every: u64,
+}
+
+pub(crate) struct SecretVaultAuditor {
+    sinks: Vec<Arc<dyn SecretVaultAuditSink>>,
+    options: SecretVaultAuditOptions,
+    access_hits_counter: AtomicU64,
+}
+
+impl SecretVaultAuditor {
+    pub fn new(
+        sinks: Vec<Arc<dyn SecretVaultAuditSink>>,
+        options: SecretVaultAuditOptions,
+    ) -> Self {
+        Self {
+            sinks,
+            options,
+            access_hits_counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn audit_access(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        principal: Option<&SecretVaultPrincipal>,
+        hit: bool,
+    ) {
+        if hit
+            && self.options.access_hits_sample_every > 1
+            && !self
+                .access_hits_counter
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(self.options.access_hits_sample_every)
+        {
+            return;
+        }
+
+        self.audit(
+            &SecretVaultAuditEvent::new(SecretVaultAuditEventKind::Access)
+                .with_key(secret_vault_key.clone())
+                .opt_principal(principal.cloned())
+                .with_hit(hit),
+        );
+    }
+
+    pub fn audit(&self, event: &SecretVaultAuditEvent) {
+        for sink in self.sinks.iter() {
+            sink.audit(event);
+        }
+    }
+}
+
+impl std::fmt::Debug for SecretVaultAuditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretVaultAuditor")
+            .field("sinks", &self.sinks.len())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Records audit events as `tracing` events with the `secret_vault::audit` target.
+pub struct SecretVaultTracingAuditSink;
+
+impl SecretVaultAuditSink for SecretVaultTracingAuditSink {
+    fn audit(&self, event: &SecretVaultAuditEvent) {
+        info!(
+            target: "secret_vault::audit",
+            kind = event.kind.as_str(),
+            timestamp = %event.timestamp.to_rfc3339(),
+            secret_name = event.key.as_ref().map(|key| key.secret_name.value().as_str()),
+            namespace = event.key.as_ref().and_then(|key| key.namespace.as_ref()).map(|namespace| namespace.value().as_str()),
+            secret_version = event.key.as_ref().and_then(|key| key.secret_version.as_ref()).map(|version| version.value().as_str()),
+            principal = event.principal.as_ref().map(|principal| principal.value().as_str()),
+            hit = event.hit,
+            version = event.version.as_ref().map(|version| version.value().as_str()),
+            "Secret vault audit event"
+        );
+    }
+}
+
+/// Appends audit events to a file in the JSON lines format.
+/// Events are written by a background thread, so audited operations don't wait for file writes.
+/// Dropping the sink waits for pending events to be written.
+#[cfg(feature = "serde")]
+pub struct SecretVaultJsonLinesAuditSink {
+    sender: Option<std::sync::mpsc::Sender<String>>,
+    writer: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "serde")]
+impl SecretVaultJsonLinesAuditSink {
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> SecretVaultResult<Self> {
+        let path = path.as_ref();
+        let mut open_options = std::fs::OpenOptions::new();
+        open_options.create(true).append(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut open_options, 0o600);
+
+        let file = open_options.open(path).map_err(|err| {
+            crate::errors::SecretVaultSystemError::create(
+                "AUDIT_FILE",
+                &format!("Unable to open audit file `{}`: {err}", path.display()),
+            )
+        })?;
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let writer = std::thread::Builder::new()
+            .name("secret-vault-audit".into())
+            .spawn(move || Self::write_events(file, receiver))
+            .map_err(|err| {
+                crate::errors::SecretVaultSystemError::create(
+                    "AUDIT_FILE",
+                    &format!("Unable to start audit file writer: {err}"),
+                )
+            })?;
+
+        Ok(Self {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    fn write_events(file: std::fs::File, receiver: std::sync::mpsc::Receiver<String>) {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(file);
+        while let Ok(line) = receiver.recv() {
+            // Lines are buffered while events keep coming and flushed when the queue is empty
+            let written = std::iter::once(line)
+                .chain(receiver.try_iter())
+                .try_for_each(|line| writer.write_all(line.as_bytes()))
+                .and_then(|_| writer.flush());
+            if let Err(err) = written {
+                warn!("Unable to write secret vault audit events: {}", err);
+            }
+        }
+    }
+
+    pub fn event_to_json(event: &SecretVaultAuditEvent) -> serde_json::Value {
+        serde_json::json!({
+            "kind": event.kind.as_str(),
+            "timestamp": event.timestamp.to_rfc3339(),
+            "secret_name": event.key.as_ref().map(|key| key.secret_name.value()),
+            "namespace": event.key.as_ref().and_then(|key| key.namespace.as_ref()).map(|namespace| namespace.value()),
+            "secret_version": event.key.as_ref().and_then(|key| key.secret_version.as_ref()).map(|version| version.value()),
+            "principal": event.principal.as_ref().map(|principal| principal.value()),
+            "hit": event.hit,
+            "version": event.version.as_ref().map(|version| version.value()),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl SecretVaultAuditSink for SecretVaultJsonLinesAuditSink {
+    fn audit(&self, event: &SecretVaultAuditEvent) {
+        let mut line = Self::event_to_json(event).to_string();
+        line.push('\n');
+
+        if let Some(sender) = &self.sender {
+            if sender.send(line).is_err() {
+                warn!("Unable to write a secret vault audit event: the writer has stopped");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Drop for SecretVaultJsonLinesAuditSink {
+    fn drop(&mut self) {
+        // Closing the channel stops the writer after pending events are written
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secret_vault_value::SecretValue;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockAuditSink {
+        events: Mutex<Vec<SecretVaultAuditEvent>>,
+    }
+
+    impl SecretVaultAuditSink for MockAuditSink {
+        fn audit(&self, event: &SecretVaultAuditEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn audit_events_test() {
+        let secret_ref = SecretVaultRef::new("secret1".into());
+        let unknown_ref = SecretVaultRef::new("unknown".into());
+        let audit_sink = Arc::new(MockAuditSink::default());
+
+        let mut vault = SecretVaultBuilder::with_source(MockSecretsSource::new(vec![(
+            secret_ref.clone(),
+            SecretValue::from("test-value"),
+        )]))
+        .with_secret_refs(vec![&secret_ref])
+        .with_audit_sink(audit_sink.clone())
+        .with_audit_options(SecretVaultAuditOptions::new().with_access_hits_sample_every(2))
+        .build()
+        .unwrap();
+
+        vault.refresh().await.unwrap();
+
+        let viewer = vault.viewer().with_principal("billing".into());
+        for _ in 0..4 {
+            viewer.get_secret_by_ref(&secret_ref).await.unwrap();
+        }
+        viewer.get_secret_by_ref(&unknown_ref).await.unwrap();
+
+        vault
+            .remove_secret_ref(&secret_ref.key)
+            .compact()
+            .await
+            .unwrap();
+
+        let events = audit_sink.events.lock().unwrap().clone();
+        let event_kinds: Vec<SecretVaultAuditEventKind> =
+            events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            event_kinds,
+            vec![
+                SecretVaultAuditEventKind::Insert,
+                SecretVaultAuditEventKind::Refresh,
+                SecretVaultAuditEventKind::Access,
+                SecretVaultAuditEventKind::Access,
+                SecretVaultAuditEventKind::Access,
+                SecretVaultAuditEventKind::Compact,
+            ]
+        );
+
+        let access_events: Vec<&SecretVaultAuditEvent> = events
+            .iter()
+            .filter(|event| event.kind == SecretVaultAuditEventKind::Access)
+            .collect();
+        assert!(access_events
+            .iter()
+            .all(|event| event.principal == Some("billing".into())));
+        assert_eq!(access_events[2].hit, Some(false));
+        assert_eq!(access_events[2].key, Some(unknown_ref.key.clone()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_lines_audit_sink_test() {
+        let audit_dir = tempfile::tempdir().unwrap();
+        let audit_file = audit_dir.path().join("audit.jsonl");
+        let audit_sink = SecretVaultJsonLinesAuditSink::new(&audit_file).unwrap();
+
+        audit_sink.audit(
+            &SecretVaultAuditEvent::new(SecretVaultAuditEventKind::Insert)
+                .with_key(SecretVaultKey::new("secret1".into()).with_namespace("aws".into()))
+                .with_version("1".into()),
+        );
+        audit_sink.audit(&SecretVaultAuditEvent::new(
+            SecretVaultAuditEventKind::Refresh,
+        ));
+
+        drop(audit_sink);
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&audit_file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["kind"], "insert");
+        assert_eq!(lines[0]["namespace"], "aws");
+        assert_eq!(lines[0]["version"], "1");
+        assert_eq!(lines[1]["secret_name"], serde_json::Value::Null);
+    }
+}
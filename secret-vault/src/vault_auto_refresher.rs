@@ -1,9 +1,12 @@
+use crate::vault_metrics::*;
+use crate::vault_tracing::*;
 use crate::*;
 use rsb_derive::*;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time::*;
 use tracing::*;
@@ -11,6 +14,7 @@ use tracing::*;
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
 pub struct SecretVaultAutoRefresherOptions {
     pub refresh_interval: Duration,
+    pub encryption_key_rotation_interval: Option<Duration>,
 }
 
 pub struct SecretVaultAutoRefresher<S, E>
@@ -23,6 +27,7 @@ where
     shutdown: Arc<AtomicBool>,
     shutdown_handle: Option<JoinHandle<()>>,
     shutdown_writer: Option<Arc<UnboundedSender<i8>>>,
+    reports: Arc<watch::Sender<Option<Arc<SecretVaultRefreshReport>>>>,
 }
 
 impl<S, E> SecretVaultAutoRefresher<S, E>
@@ -37,9 +42,16 @@ where
             shutdown: Arc::new(AtomicBool::new(false)),
             shutdown_handle: None,
             shutdown_writer: None,
+            reports: Arc::new(watch::Sender::new(None)),
         }
     }
 
+    /// Subscribes to reports of automatic refreshes. Failures of some secrets don't stop
+    /// refreshing other secrets, and failed secrets keep their cached values.
+    pub fn subscribe_reports(&self) -> watch::Receiver<Option<Arc<SecretVaultRefreshReport>>> {
+        self.reports.subscribe()
+    }
+
     pub async fn start(&mut self) -> SecretVaultResult<()> {
         info!(
             "Starting SecretVault automatic refreshing: {:?}",
@@ -56,6 +68,7 @@ where
             self.options.clone(),
             self.shutdown.clone(),
             rx,
+            self.reports.clone(),
         )));
 
         Ok(())
@@ -80,10 +93,24 @@ where
         options: SecretVaultAutoRefresherOptions,
         shutdown_flag: Arc<AtomicBool>,
         mut shutdown_receiver: UnboundedReceiver<i8>,
+        reports: Arc<watch::Sender<Option<Arc<SecretVaultRefreshReport>>>>,
     ) {
         let mut interval = interval(options.refresh_interval);
         interval.tick().await;
 
+        let mut key_rotation_interval = interval_at(
+            Instant::now()
+                + options
+                    .encryption_key_rotation_interval
+                    .unwrap_or(options.refresh_interval),
+            options
+                .encryption_key_rotation_interval
+                .unwrap_or(options.refresh_interval),
+        );
+
+        let mut refresh_attempt: u64 = 0;
+        let mut key_rotation_attempt: u64 = 0;
+
         loop {
             tokio::select! {
                 _ = shutdown_receiver.recv() => {
@@ -97,10 +124,51 @@ where
                         shutdown_receiver.close();
                         break;
                     }
-                    match vault.refresh_only(|secret_ref| secret_ref.auto_refresh).await {
-                        Ok(_) => {},
+                    refresh_attempt += 1;
+                    match traced(
+                        auto_refresh_span("refresh", refresh_attempt),
+                        vault.refresh_only_with_report(|secret_ref| secret_ref.auto_refresh),
+                    )
+                    .await
+                    {
+                        Ok(report) if report.is_complete() => {
+                            refresh_attempt = 0;
+                            reports.send_replace(Some(Arc::new(report)));
+                        }
+                        Ok(report) => {
+                            warn!(
+                                "Automatic refresh vault partially failed (attempt {}). Failed: {}. Missing: {}",
+                                refresh_attempt,
+                                report.failed().len(),
+                                report.missing().len()
+                            );
+                            record_auto_refresh_failure("refresh");
+                            reports.send_replace(Some(Arc::new(report)));
+                        }
+                        Err(err) => {
+                            warn!(
+                                "Automatic refresh vault error (attempt {}): {}",
+                                refresh_attempt, err
+                            );
+                            record_auto_refresh_failure("refresh");
+                        }
+                    }
+                }
+                _ = key_rotation_interval.tick(), if options.encryption_key_rotation_interval.is_some() => {
+                    key_rotation_attempt += 1;
+                    match traced(
+                        auto_refresh_span("key_rotation", key_rotation_attempt),
+                        vault.rotate_encryption_key(),
+                    )
+                    .await
+                    {
+                        Ok(_) => key_rotation_attempt = 0,
                         Err(err) => {
-                            warn!("Automatic refresh vault error: {}", err);
+                            warn!(
+                                "Automatic vault encryption key rotation error (attempt {}): {}",
+                                key_rotation_attempt, err
+                            );
+                            record_auto_refresh_failure("key_rotation");
                         }
                     }
                 }
@@ -133,6 +201,19 @@ mod tests {
             .map(|secret_ref| secret_ref.with_auto_refresh(true))
             .collect();
 
+        // Mock sources find secrets by whole refs including auto_refresh
+        let mock_secrets_store = MockSecretsSource::new(
+            secret_refs
+                .iter()
+                .map(|secret_ref| {
+                    let secret_value = mock_secrets_store
+                        .get(&secret_ref.clone().with_auto_refresh(false))
+                        .unwrap();
+                    (secret_ref.clone(), secret_value)
+                })
+                .collect(),
+        );
+
         let vault = Arc::new(
             SecretVaultBuilder::with_source(mock_secrets_store.clone())
                 .build()
@@ -145,12 +226,17 @@ mod tests {
             SecretVaultAutoRefresherOptions::new(Duration::from_millis(50)),
         );
 
+        let reports = refresher.subscribe_reports();
         refresher.start().await.unwrap();
 
         tokio::time::sleep(Duration::from_millis(500)).await;
 
         refresher.shutdown().await.unwrap();
 
+        let report = reports.borrow().clone().unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.outcomes.len(), secret_refs.len());
+
         for secret_ref in secret_refs {
             assert_eq!(
                 vault
@@ -159,7 +245,7 @@ mod tests {
                     .unwrap()
                     .map(|secret| secret.value)
                     .as_ref(),
-                mock_secrets_store.get(&secret_ref).as_ref()
+                Some(&mock_secrets_store.get(&secret_ref).unwrap())
             )
         }
     }
@@ -1,4 +1,7 @@
+use crate::vault_audit::SecretVaultAuditor;
+use crate::vault_store::SecretVaultStore;
 use crate::*;
+use std::sync::Arc;
 
 pub struct SecretVaultBuilder<
     S: SecretsSource + Sync + Send,
@@ -7,6 +10,9 @@ pub struct SecretVaultBuilder<
     source: S,
     encryption: E,
     refs: Vec<SecretVaultRef>,
+    retained_versions: usize,
+    audit_sinks: Vec<Arc<dyn SecretVaultAuditSink>>,
+    audit_options: SecretVaultAuditOptions,
 }
 
 impl<S> SecretVaultBuilder<S, SecretVaultNoEncryption>
@@ -18,6 +24,9 @@ where
             source,
             encryption: SecretVaultNoEncryption {},
             refs: Vec::new(),
+            retained_versions: 0,
+            audit_sinks: Vec::new(),
+            audit_options: SecretVaultAuditOptions::new(),
         }
     }
 }
@@ -35,6 +44,9 @@ where
             source: self.source,
             encryption,
             refs: Vec::new(),
+            retained_versions: self.retained_versions,
+            audit_sinks: self.audit_sinks,
+            audit_options: self.audit_options,
         }
     }
 
@@ -46,6 +58,9 @@ where
             source: self.source,
             encryption: SecretVaultNoEncryption {},
             refs: Vec::new(),
+            retained_versions: self.retained_versions,
+            audit_sinks: self.audit_sinks,
+            audit_options: self.audit_options,
         }
     }
 
@@ -54,11 +69,47 @@ where
             source: self.source,
             encryption: self.encryption,
             refs: secret_refs.into_iter().cloned().collect(),
+            retained_versions: self.retained_versions,
+            audit_sinks: self.audit_sinks,
+            audit_options: self.audit_options,
+        }
+    }
+
+    /// Keeps up to `retained_versions` previous versions of every secret in the vault after refreshes.
+    pub fn with_retained_versions(self, retained_versions: usize) -> SecretVaultBuilder<S, E> {
+        SecretVaultBuilder {
+            retained_versions,
+            ..self
+        }
+    }
+
+    /// Adds a sink receiving audit events for secret reads and vault changes.
+    pub fn with_audit_sink(mut self, audit_sink: Arc<dyn SecretVaultAuditSink>) -> Self {
+        self.audit_sinks.push(audit_sink);
+        self
+    }
+
+    pub fn with_audit_options(self, audit_options: SecretVaultAuditOptions) -> Self {
+        Self {
+            audit_options,
+            ..self
         }
     }
 
     pub fn build(self) -> SecretVaultResult<SecretVault<S, E>> {
-        let vault = SecretVault::new(self.source, self.encryption)?;
+        let vault = SecretVault::with_store(
+            self.source,
+            SecretVaultStore::new(self.encryption)
+                .with_retained_versions(self.retained_versions)
+                .with_auditor(if !self.audit_sinks.is_empty() {
+                    Some(SecretVaultAuditor::new(
+                        self.audit_sinks,
+                        self.audit_options,
+                    ))
+                } else {
+                    None
+                }),
+        )?;
 
         Ok(if !self.refs.is_empty() {
             vault.with_secret_refs(self.refs.iter().collect())
@@ -0,0 +1,126 @@
+use crate::errors::*;
+use crate::secret_decode::decode_secret_value;
+use crate::*;
+use async_trait::async_trait;
+
+/// A typed secrets struct, usually implemented with `#[derive(SecretVaultConfig)]` (`derive` feature).
+#[async_trait]
+pub trait SecretVaultConfig: Sized + Send {
+    /// Secret refs of all fields to register in the vault.
+    fn secret_refs() -> Vec<SecretVaultRef>;
+
+    async fn load_from<V>(view: &V) -> SecretVaultResult<Self>
+    where
+        V: SecretVaultView + Send + Sync;
+}
+
+/// Loading a field of a typed secrets struct from a vault view.
+#[async_trait]
+pub trait SecretVaultConfigField: Sized + Send {
+    async fn load_field<V>(
+        view: &V,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Self>>
+    where
+        V: SecretVaultView + Send + Sync;
+
+    async fn require_field<V>(view: &V, secret_ref: &SecretVaultRef) -> SecretVaultResult<Self>
+    where
+        V: SecretVaultView + Send + Sync,
+    {
+        match Self::load_field(view, secret_ref).await? {
+            Some(value) => Ok(value),
+            None => Err(SecretVaultError::DataNotFoundError(
+                SecretVaultDataNotFoundError::new(
+                    SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
+                    format!("Secret {secret_ref:?} doesn't exist in vault but was required"),
+                ),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: SecretDecode> SecretVaultConfigField for T {
+    async fn load_field<V>(view: &V, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Self>>
+    where
+        V: SecretVaultView + Send + Sync,
+    {
+        view.with_secret_by_ref(secret_ref, |secret_value, _| {
+            decode_secret_value::<T>(&secret_ref.key, secret_value)
+        })
+        .await?
+        .transpose()
+    }
+}
+
+#[async_trait]
+impl SecretVaultConfigField for Secret {
+    async fn load_field<V>(view: &V, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Self>>
+    where
+        V: SecretVaultView + Send + Sync,
+    {
+        view.get_secret_by_ref(secret_ref).await
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::*;
+    use secret_vault_value::SecretValue;
+
+    #[derive(SecretVaultConfig)]
+    struct TestSecrets {
+        #[secret(name = "db-password", namespace = "test", version = "1", auto_refresh)]
+        db_password: SecretValue,
+        #[secret(name = "api-port", allow_in_snapshots)]
+        api_port: u16,
+        api_token: Secret,
+        #[secret(name = "optional-token")]
+        optional_token: Option<String>,
+    }
+
+    #[test]
+    fn secret_refs_test() {
+        let secret_refs = TestSecrets::secret_refs();
+
+        assert_eq!(
+            secret_refs[0],
+            SecretVaultRef::new("db-password".into())
+                .with_namespace("test".into())
+                .with_secret_version("1".into())
+                .with_auto_refresh(true)
+        );
+        assert_eq!(
+            secret_refs[1],
+            SecretVaultRef::new("api-port".into()).with_allow_in_snapshots(true)
+        );
+        assert_eq!(secret_refs[2], SecretVaultRef::new("api_token".into()));
+        assert_eq!(
+            secret_refs[3],
+            SecretVaultRef::new("optional-token".into()).with_required(false)
+        );
+    }
+
+    #[tokio::test]
+    async fn load_from_test() {
+        let secret_refs = TestSecrets::secret_refs();
+        let mock_secrets_store = MockSecretsSource::new(vec![
+            (secret_refs[0].clone(), SecretValue::from("test-password")),
+            (secret_refs[1].clone(), SecretValue::from("8080")),
+            (secret_refs[2].clone(), SecretValue::from("test-token")),
+        ]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store)
+            .with_secret_refs(secret_refs.iter().collect())
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        let secrets = TestSecrets::load_from(&vault).await.unwrap();
+        assert_eq!(secrets.db_password, SecretValue::from("test-password"));
+        assert_eq!(secrets.api_port, 8080);
+        assert_eq!(secrets.api_token.value, SecretValue::from("test-token"));
+        assert_eq!(secrets.optional_token, None);
+    }
+}
@@ -0,0 +1,713 @@
+use crate::errors::*;
+use crate::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+pub type BoxedSecretsSource = Box<dyn SecretsSource + Send + Sync>;
+pub type BoxedSecretVaultEncryption = Box<dyn SecretVaultEncryption + Send + Sync>;
+
+/// Declarative vault configuration read from TOML, YAML or JSON documents.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretVaultFileConfig {
+    pub sources: Vec<SecretVaultSourceFileConfig>,
+    #[serde(default)]
+    pub encryption: SecretVaultEncryptionFileConfig,
+    pub refresh: Option<SecretVaultRefreshFileConfig>,
+    #[serde(default)]
+    pub retained_versions: usize,
+    #[serde(default)]
+    pub secrets: Vec<SecretVaultRefFileConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum SecretVaultSourceFileConfig {
+    Env {
+        namespace: Option<String>,
+    },
+    Files {
+        namespace: Option<String>,
+        root_path: Option<String>,
+    },
+    AwsSecretmanager {
+        namespace: Option<String>,
+        account_id: String,
+        region: Option<String>,
+        #[serde(default)]
+        read_metadata: bool,
+    },
+    GcpSecretmanager {
+        namespace: Option<String>,
+        project_id: String,
+        #[serde(default)]
+        read_metadata: bool,
+    },
+    TempGen {
+        namespace: Option<String>,
+        secrets: Vec<TempSecretFileConfig>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TempSecretFileConfig {
+    pub name: String,
+    #[serde(default)]
+    pub kind: TempSecretKindFileConfig,
+    pub key_len: Option<usize>,
+    #[serde(default = "default_true")]
+    pub printable: bool,
+    #[serde(default)]
+    pub regenerate_on_refresh: bool,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TempSecretKindFileConfig {
+    #[default]
+    RandomBytes,
+    Password,
+    TokenBase64Url,
+    TokenAlphanumeric,
+    UuidV4,
+    Ed25519KeyPair,
+    EcdsaP256KeyPair,
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum SecretVaultEncryptionFileConfig {
+    #[default]
+    None,
+    Ring,
+    AwsKms {
+        account_id: String,
+        key_id: String,
+        region: Option<String>,
+    },
+    GcpKms {
+        project_id: String,
+        location: String,
+        key_ring: String,
+        key: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretVaultRefreshFileConfig {
+    pub interval_secs: u64,
+    pub encryption_key_rotation_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretVaultRefFileConfig {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub version: Option<String>,
+    #[serde(default = "default_true")]
+    pub required: bool,
+    #[serde(default)]
+    pub auto_refresh: bool,
+    #[serde(default)]
+    pub allow_in_snapshots: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn config_error(path: &str, error: &str) -> SecretVaultError {
+    SecretVaultInvalidParametersError::create(path, error)
+}
+
+fn parse_error<E: std::fmt::Display>(err: serde_path_to_error::Error<E>) -> SecretVaultError {
+    let path = err.path().to_string();
+    config_error(&path, &err.into_inner().to_string())
+}
+
+impl SecretVaultSourceFileConfig {
+    pub fn namespace(&self) -> Option<&String> {
+        match self {
+            SecretVaultSourceFileConfig::Env { namespace }
+            | SecretVaultSourceFileConfig::Files { namespace, .. }
+            | SecretVaultSourceFileConfig::AwsSecretmanager { namespace, .. }
+            | SecretVaultSourceFileConfig::GcpSecretmanager { namespace, .. }
+            | SecretVaultSourceFileConfig::TempGen { namespace, .. } => namespace.as_ref(),
+        }
+    }
+
+    fn required_feature(&self) -> Option<&'static str> {
+        match self {
+            SecretVaultSourceFileConfig::AwsSecretmanager { .. }
+                if !cfg!(feature = "aws-secretmanager") =>
+            {
+                Some("aws-secretmanager")
+            }
+            SecretVaultSourceFileConfig::GcpSecretmanager { .. }
+                if !cfg!(feature = "gcp-secretmanager") =>
+            {
+                Some("gcp-secretmanager")
+            }
+            SecretVaultSourceFileConfig::TempGen { .. }
+                if !cfg!(feature = "ring-aead-encryption") =>
+            {
+                Some("ring-aead-encryption")
+            }
+            _ => None,
+        }
+    }
+
+    async fn create_source(&self) -> SecretVaultResult<BoxedSecretsSource> {
+        match self {
+            SecretVaultSourceFileConfig::Env { .. } => Ok(Box::new(InsecureEnvSource::new())),
+            SecretVaultSourceFileConfig::Files { root_path, .. } => {
+                Ok(Box::new(FilesSource::with_options(
+                    FilesSourceOptions::new()
+                        .opt_root_path(root_path.as_ref().map(|path| Path::new(path).into())),
+                )))
+            }
+            #[cfg(feature = "aws-secretmanager")]
+            SecretVaultSourceFileConfig::AwsSecretmanager {
+                account_id,
+                region,
+                read_metadata,
+                ..
+            } => Ok(Box::new(
+                crate::aws::AwsSecretManagerSource::with_options(
+                    crate::aws::AwsSecretManagerSourceOptions::new(account_id.clone())
+                        .opt_region(
+                            region
+                                .clone()
+                                .map(aws_sdk_secretsmanager::config::Region::new),
+                        )
+                        .with_read_metadata(*read_metadata),
+                )
+                .await?,
+            )),
+            #[cfg(feature = "gcp-secretmanager")]
+            SecretVaultSourceFileConfig::GcpSecretmanager {
+                project_id,
+                read_metadata,
+                ..
+            } => Ok(Box::new(
+                crate::gcp::GcpSecretManagerSource::with_options(
+                    crate::gcp::GcpSecretManagerSourceOptions::new(project_id.clone())
+                        .with_read_metadata(*read_metadata),
+                )
+                .await?,
+            )),
+            #[cfg(feature = "ring-aead-encryption")]
+            SecretVaultSourceFileConfig::TempGen { namespace, secrets } => {
+                let mut options = TempSecretGenSourceOptions::new();
+                for secret in secrets {
+                    options = options.add_secret_generator(
+                        &SecretVaultKey::new(secret.name.clone().into())
+                            .opt_namespace(namespace.clone().map(SecretNamespace::new)),
+                        secret.to_temp_secret_options(),
+                    );
+                }
+                Ok(Box::new(TempSecretGenSource::with_options(options)?))
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(config_error(
+                "sources",
+                &format!(
+                    "Source requires the '{}' feature",
+                    other.required_feature().unwrap_or_default()
+                ),
+            )),
+        }
+    }
+}
+
+impl TempSecretKindFileConfig {
+    fn requires_key_len(&self) -> bool {
+        matches!(
+            self,
+            TempSecretKindFileConfig::RandomBytes
+                | TempSecretKindFileConfig::Password
+                | TempSecretKindFileConfig::TokenBase64Url
+                | TempSecretKindFileConfig::TokenAlphanumeric
+        )
+    }
+}
+
+#[cfg(feature = "ring-aead-encryption")]
+impl TempSecretFileConfig {
+    fn to_temp_secret_options(&self) -> TempSecretOptions {
+        let key_len = self.key_len.unwrap_or_default();
+        let kind = match self.kind {
+            TempSecretKindFileConfig::RandomBytes => TempSecretGeneratorKind::RandomBytes,
+            TempSecretKindFileConfig::Password => {
+                TempSecretGeneratorKind::Password(PasswordPolicy::new(key_len))
+            }
+            TempSecretKindFileConfig::TokenBase64Url => {
+                TempSecretGeneratorKind::Token(TokenAlphabet::Base64Url)
+            }
+            TempSecretKindFileConfig::TokenAlphanumeric => {
+                TempSecretGeneratorKind::Token(TokenAlphabet::Alphanumeric)
+            }
+            TempSecretKindFileConfig::UuidV4 => TempSecretGeneratorKind::UuidV4,
+            TempSecretKindFileConfig::Ed25519KeyPair => TempSecretGeneratorKind::Ed25519KeyPair,
+            TempSecretKindFileConfig::EcdsaP256KeyPair => TempSecretGeneratorKind::EcdsaP256KeyPair,
+            TempSecretKindFileConfig::HmacSha256 => {
+                TempSecretGeneratorKind::HmacKey(HmacKeyAlgorithm::Sha256)
+            }
+            TempSecretKindFileConfig::HmacSha384 => {
+                TempSecretGeneratorKind::HmacKey(HmacKeyAlgorithm::Sha384)
+            }
+            TempSecretKindFileConfig::HmacSha512 => {
+                TempSecretGeneratorKind::HmacKey(HmacKeyAlgorithm::Sha512)
+            }
+        };
+
+        TempSecretOptions::new(key_len)
+            .with_kind(kind)
+            .with_printable(self.printable)
+            .with_regenerate_on_refresh(self.regenerate_on_refresh)
+    }
+}
+
+impl SecretVaultEncryptionFileConfig {
+    fn required_feature(&self) -> Option<&'static str> {
+        match self {
+            SecretVaultEncryptionFileConfig::Ring if !cfg!(feature = "ring-aead-encryption") => {
+                Some("ring-aead-encryption")
+            }
+            SecretVaultEncryptionFileConfig::AwsKms { .. } if !cfg!(feature = "aws-kms") => {
+                Some("aws-kms-encryption")
+            }
+            SecretVaultEncryptionFileConfig::GcpKms { .. } if !cfg!(feature = "gcp-kms") => {
+                Some("gcp-kms")
+            }
+            _ => None,
+        }
+    }
+
+    async fn create_encryption(&self) -> SecretVaultResult<BoxedSecretVaultEncryption> {
+        match self {
+            SecretVaultEncryptionFileConfig::None => Ok(Box::new(SecretVaultNoEncryption)),
+            #[cfg(feature = "ring-aead-encryption")]
+            SecretVaultEncryptionFileConfig::Ring => Ok(Box::new(
+                crate::ring_encryption::SecretVaultRingAeadEncryption::new()?,
+            )),
+            #[cfg(feature = "aws-kms")]
+            SecretVaultEncryptionFileConfig::AwsKms {
+                account_id,
+                key_id,
+                region,
+            } => Ok(Box::new(
+                crate::aws::AwsKmsEnvelopeEncryption::new(&crate::aws::AwsKmsKeyRef {
+                    account_id: account_id.clone(),
+                    key_id: key_id.clone(),
+                    aws_region: region.clone().map(aws_sdk_kms::config::Region::new),
+                })
+                .await?,
+            )),
+            #[cfg(feature = "gcp-kms")]
+            SecretVaultEncryptionFileConfig::GcpKms {
+                project_id,
+                location,
+                key_ring,
+                key,
+            } => Ok(Box::new(
+                crate::gcp::GcpKmsEnvelopeEncryption::new(&crate::gcp::GcpKmsKeyRef {
+                    google_project_id: project_id.clone(),
+                    location: location.clone(),
+                    key_ring: key_ring.clone(),
+                    key: key.clone(),
+                })
+                .await?,
+            )),
+            #[allow(unreachable_patterns)]
+            other => Err(config_error(
+                "encryption.type",
+                &format!(
+                    "Encryption requires the '{}' feature",
+                    other.required_feature().unwrap_or_default()
+                ),
+            )),
+        }
+    }
+}
+
+impl SecretVaultRefFileConfig {
+    pub fn to_secret_ref(&self) -> SecretVaultRef {
+        SecretVaultRef::new(self.name.clone().into())
+            .opt_namespace(self.namespace.clone().map(SecretNamespace::new))
+            .opt_secret_version(self.version.clone().map(SecretVersion::new))
+            .with_required(self.required)
+            .with_auto_refresh(self.auto_refresh)
+            .with_allow_in_snapshots(self.allow_in_snapshots)
+    }
+}
+
+impl SecretVaultFileConfig {
+    pub fn from_toml_str(config: &str) -> SecretVaultResult<Self> {
+        let deserializer = toml::Deserializer::new(config);
+        let config: Self = serde_path_to_error::deserialize(deserializer).map_err(parse_error)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "config-yaml")]
+    pub fn from_yaml_str(config: &str) -> SecretVaultResult<Self> {
+        let deserializer = serde_yaml::Deserializer::from_str(config);
+        let config: Self = serde_path_to_error::deserialize(deserializer).map_err(parse_error)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_json_str(config: &str) -> SecretVaultResult<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(config);
+        let config: Self =
+            serde_path_to_error::deserialize(&mut deserializer).map_err(parse_error)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads a configuration file. The format is detected by the file extension
+    /// (`.toml`, `.json` or `.yaml`/`.yml` with the `config-yaml` feature).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> SecretVaultResult<Self> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(|err| {
+            config_error(
+                &path.display().to_string(),
+                &format!("Unable to read config file: {err}"),
+            )
+        })?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml_str(&config),
+            #[cfg(feature = "config-yaml")]
+            Some("yaml") | Some("yml") => Self::from_yaml_str(&config),
+            #[cfg(not(feature = "config-yaml"))]
+            Some("yaml") | Some("yml") => Err(config_error(
+                &path.display().to_string(),
+                "YAML config files require the `config-yaml` feature",
+            )),
+            Some("json") => Self::from_json_str(&config),
+            _ => Err(config_error(
+                &path.display().to_string(),
+                "Unknown config file format, expected .toml, .yaml, .yml or .json",
+            )),
+        }
+    }
+
+    /// Checks the configuration before creating any sources.
+    /// Errors contain the path of the invalid field, e.g. `secrets[2].namespace`.
+    pub fn validate(&self) -> SecretVaultResult<()> {
+        if self.sources.is_empty() {
+            return Err(config_error("sources", "At least one source is required"));
+        }
+
+        let multiple_sources = self.is_multiple_sources();
+        let mut namespaces: HashSet<&String> = HashSet::new();
+
+        for (index, source) in self.sources.iter().enumerate() {
+            if let Some(feature) = source.required_feature() {
+                return Err(config_error(
+                    &format!("sources[{index}].type"),
+                    &format!("Source requires the '{feature}' feature"),
+                ));
+            }
+            match source.namespace() {
+                Some(namespace) if !namespaces.insert(namespace) => {
+                    return Err(config_error(
+                        &format!("sources[{index}].namespace"),
+                        &format!("Duplicate source namespace '{namespace}'"),
+                    ));
+                }
+                None if multiple_sources => {
+                    return Err(config_error(
+                        &format!("sources[{index}].namespace"),
+                        "Namespace is required when multiple sources or namespaces are used",
+                    ));
+                }
+                _ => {}
+            }
+            if let SecretVaultSourceFileConfig::TempGen { secrets, .. } = source {
+                for (secret_index, secret) in secrets.iter().enumerate() {
+                    if secret.name.is_empty() {
+                        return Err(config_error(
+                            &format!("sources[{index}].secrets[{secret_index}].name"),
+                            "Secret name must not be empty",
+                        ));
+                    }
+                    if secret.kind.requires_key_len() && secret.key_len.unwrap_or_default() == 0 {
+                        return Err(config_error(
+                            &format!("sources[{index}].secrets[{secret_index}].key_len"),
+                            "Key length must be specified and greater than zero for this kind of secrets",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(feature) = self.encryption.required_feature() {
+            return Err(config_error(
+                "encryption.type",
+                &format!("Encryption requires the '{feature}' feature"),
+            ));
+        }
+
+        if let Some(refresh) = &self.refresh {
+            if refresh.interval_secs == 0 {
+                return Err(config_error(
+                    "refresh.interval_secs",
+                    "Refresh interval must be greater than zero",
+                ));
+            }
+            if refresh.encryption_key_rotation_interval_secs == Some(0) {
+                return Err(config_error(
+                    "refresh.encryption_key_rotation_interval_secs",
+                    "Encryption key rotation interval must be greater than zero",
+                ));
+            }
+        }
+
+        let mut secret_keys: HashSet<SecretVaultKey> = HashSet::new();
+        for (index, secret) in self.secrets.iter().enumerate() {
+            if secret.name.is_empty() {
+                return Err(config_error(
+                    &format!("secrets[{index}].name"),
+                    "Secret name must not be empty",
+                ));
+            }
+            match &secret.namespace {
+                Some(namespace) if multiple_sources && !namespaces.contains(namespace) => {
+                    return Err(config_error(
+                        &format!("secrets[{index}].namespace"),
+                        &format!("Namespace '{namespace}' isn't defined by any source"),
+                    ));
+                }
+                None if multiple_sources => {
+                    return Err(config_error(
+                        &format!("secrets[{index}].namespace"),
+                        "Namespace is required when multiple sources or namespaces are used",
+                    ));
+                }
+                _ => {}
+            }
+            if !secret_keys.insert(secret.to_secret_ref().key) {
+                return Err(config_error(
+                    &format!("secrets[{index}]"),
+                    &format!("Duplicate secret '{}'", secret.name),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_multiple_sources(&self) -> bool {
+        self.sources.len() > 1
+            || self
+                .sources
+                .iter()
+                .any(|source| source.namespace().is_some())
+    }
+
+    pub fn secret_refs(&self) -> Vec<SecretVaultRef> {
+        self.secrets
+            .iter()
+            .map(|secret| secret.to_secret_ref())
+            .collect()
+    }
+
+    pub fn auto_refresher_options(&self) -> Option<SecretVaultAutoRefresherOptions> {
+        self.refresh.as_ref().map(|refresh| {
+            SecretVaultAutoRefresherOptions::new(Duration::from_secs(refresh.interval_secs))
+                .opt_encryption_key_rotation_interval(
+                    refresh
+                        .encryption_key_rotation_interval_secs
+                        .map(Duration::from_secs),
+                )
+        })
+    }
+
+    async fn create_source(&self) -> SecretVaultResult<BoxedSecretsSource> {
+        if self.is_multiple_sources() {
+            let mut multiple_sources = MultipleSecretsSources::new();
+            for source in self.sources.iter() {
+                let namespace =
+                    SecretNamespace::new(source.namespace().cloned().unwrap_or_default());
+                multiple_sources =
+                    multiple_sources.add_source(&namespace, source.create_source().await?);
+            }
+            Ok(Box::new(multiple_sources))
+        } else {
+            self.sources[0].create_source().await
+        }
+    }
+}
+
+impl SecretVaultBuilder<BoxedSecretsSource, BoxedSecretVaultEncryption> {
+    /// Creates sources and encryption described by the configuration and registers its secrets.
+    /// Automatic refresh options are available with `SecretVaultFileConfig::auto_refresher_options`.
+    pub async fn from_config(config: &SecretVaultFileConfig) -> SecretVaultResult<Self> {
+        config.validate()?;
+
+        let secret_refs = config.secret_refs();
+
+        Ok(
+            SecretVaultBuilder::with_source(config.create_source().await?)
+                .with_encryption(config.encryption.create_encryption().await?)
+                .with_secret_refs(secret_refs.iter().collect())
+                .with_retained_versions(config.retained_versions),
+        )
+    }
+
+    pub async fn from_config_file<P: AsRef<Path>>(path: P) -> SecretVaultResult<Self> {
+        Self::from_config(&SecretVaultFileConfig::from_file(path)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CONFIG_TOML: &str = r#"
+retained_versions = 1
+
+[[sources]]
+type = "env"
+namespace = "env"
+
+[[sources]]
+type = "files"
+namespace = "k8s"
+root_path = "/var/run/secrets"
+
+[refresh]
+interval_secs = 60
+
+[[secrets]]
+name = "db-password"
+namespace = "k8s"
+auto_refresh = true
+
+[[secrets]]
+name = "API_TOKEN"
+namespace = "env"
+required = false
+"#;
+
+    fn invalid_field(result: SecretVaultResult<SecretVaultFileConfig>) -> String {
+        match result {
+            Err(SecretVaultError::InvalidParametersError(err)) => err.public.field,
+            other => panic!("Unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_config_formats_test() {
+        let toml_config = SecretVaultFileConfig::from_toml_str(TEST_CONFIG_TOML).unwrap();
+        assert_eq!(
+            toml_config.secret_refs(),
+            vec![
+                SecretVaultRef::new("db-password".into())
+                    .with_namespace("k8s".into())
+                    .with_auto_refresh(true),
+                SecretVaultRef::new("API_TOKEN".into())
+                    .with_namespace("env".into())
+                    .with_required(false),
+            ]
+        );
+        assert_eq!(
+            toml_config.auto_refresher_options(),
+            Some(SecretVaultAutoRefresherOptions::new(Duration::from_secs(
+                60
+            )))
+        );
+
+        #[cfg(feature = "config-yaml")]
+        {
+            let yaml_config = SecretVaultFileConfig::from_yaml_str(
+                "sources:\n  - type: env\nsecrets:\n  - name: API_TOKEN\n",
+            )
+            .unwrap();
+            assert_eq!(
+                yaml_config.secret_refs(),
+                vec![SecretVaultRef::new("API_TOKEN".into())]
+            );
+        }
+
+        let json_config = SecretVaultFileConfig::from_json_str(
+            r#"{"sources": [{"type": "env"}], "encryption": {"type": "none"}, "secrets": [{"name": "API_TOKEN"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(json_config.secrets.len(), 1);
+    }
+
+    #[test]
+    fn config_error_locations_test() {
+        assert_eq!(
+            invalid_field(SecretVaultFileConfig::from_toml_str(
+                "[[sources]]\ntype = \"unknown\"\n"
+            )),
+            "sources[0].type"
+        );
+        #[cfg(feature = "config-yaml")]
+        assert_eq!(
+            invalid_field(SecretVaultFileConfig::from_yaml_str(
+                "sources:\n  - type: env\nsecrets:\n  - name: test\n    required: maybe\n"
+            )),
+            "secrets[0].required"
+        );
+        assert_eq!(
+            invalid_field(SecretVaultFileConfig::from_toml_str(
+                &TEST_CONFIG_TOML.replace(
+                    "namespace = \"env\"\nrequired",
+                    "namespace = \"aws\"\nrequired"
+                )
+            )),
+            "secrets[1].namespace"
+        );
+        assert_eq!(
+            invalid_field(SecretVaultFileConfig::from_toml_str(
+                "[[sources]]\ntype = \"env\"\n[[sources]]\ntype = \"files\"\n"
+            )),
+            "sources[0].namespace"
+        );
+        assert_eq!(
+            invalid_field(SecretVaultFileConfig::from_json_str(
+                r#"{"sources": [{"type": "env"}], "refresh": {"interval_secs": 0}}"#
+            )),
+            "refresh.interval_secs"
+        );
+    }
+
+    #[tokio::test]
+    async fn from_config_test() {
+        std::env::set_var("SECRET_VAULT_CONFIG_TEST", "test-value");
+
+        let config = SecretVaultFileConfig::from_toml_str(
+            "[[sources]]\ntype = \"env\"\n[[secrets]]\nname = \"SECRET_VAULT_CONFIG_TEST\"\n",
+        )
+        .unwrap();
+        let vault = SecretVaultBuilder::from_config(&config)
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        assert_eq!(
+            vault
+                .require_secret(&"SECRET_VAULT_CONFIG_TEST".into())
+                .await
+                .unwrap()
+                .value,
+            secret_vault_value::SecretValue::from("test-value")
+        );
+    }
+}
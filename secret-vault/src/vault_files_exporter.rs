@@ -0,0 +1,303 @@
+use crate::errors::*;
+use crate::*;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::*;
+
+/// Exports vault secrets as files (e.g. to a tmpfs directory) for processes reading secrets from files.
+/// Files are named the same way `FilesSource` reads them and written atomically with the file mode
+/// and the owner from `FilesSourceOptions`.
+/// Exported files are updated when the vault signals changes and removed when secrets
+/// are removed from the vault (e.g. by `compact`) or when the exporter is shut down.
+pub struct SecretVaultFilesExporter {
+    files_source: Arc<FilesSource>,
+    secret_refs: Arc<Vec<SecretVaultRef>>,
+    shutdown_sender: Option<watch::Sender<bool>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SecretVaultFilesExporter {
+    pub fn new(
+        options: FilesSourceOptions,
+        secret_refs: Vec<&SecretVaultRef>,
+    ) -> SecretVaultResult<Self> {
+        if options.root_path.is_none() {
+            return Err(SecretVaultInvalidParametersError::create(
+                "root_path",
+                "Root path is required to export secrets",
+            ));
+        }
+
+        Ok(Self {
+            files_source: Arc::new(FilesSource::with_options(options)),
+            secret_refs: Arc::new(secret_refs.into_iter().cloned().collect()),
+            shutdown_sender: None,
+            handle: None,
+        })
+    }
+
+    /// Writes the current secrets from the view once.
+    pub async fn export<V>(&self, view: &V) -> SecretVaultResult<()>
+    where
+        V: SecretVaultView + Send + Sync,
+    {
+        export_secret_files(&self.files_source, &self.secret_refs, view).await
+    }
+
+    /// Exports secrets and keeps them updated each time the vault signals changes
+    /// (see `SecretVault::subscribe_changes`).
+    pub async fn start<V>(
+        &mut self,
+        view: V,
+        mut changes: watch::Receiver<u64>,
+    ) -> SecretVaultResult<()>
+    where
+        V: SecretVaultView + Send + Sync + 'static,
+    {
+        changes.borrow_and_update();
+        self.export(&view).await?;
+
+        info!(
+            "Started exporting {} secrets to files",
+            self.secret_refs.len()
+        );
+
+        let (shutdown_sender, mut shutdown_receiver) = watch::channel(false);
+        let files_source = self.files_source.clone();
+        let secret_refs = self.secret_refs.clone();
+
+        self.shutdown_sender = Some(shutdown_sender);
+        self.handle = Some(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_receiver.changed() => {
+                        trace!("Exiting from secret files exporter...");
+                        break;
+                    },
+                    changed = changes.changed() => {
+                        if changed.is_err() {
+                            debug!("Secret vault has been dropped. Stopping exporting secret files");
+                            break;
+                        }
+                        if let Err(err) = export_secret_files(&files_source, &secret_refs, &view).await {
+                            warn!("Unable to export secret files: {}", err);
+                        }
+                    }
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Stops updating files and removes all exported files.
+    pub async fn shutdown(&mut self) -> SecretVaultResult<()> {
+        debug!("Shutting down secret files exporter ...");
+
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            shutdown_sender.send_replace(true);
+        }
+        if let Some(handle) = self.handle.take() {
+            handle.await.expect("The task being joined has panicked");
+        }
+
+        for secret_ref in self.secret_refs.iter() {
+            remove_secret_file(&self.files_source, secret_ref)?;
+        }
+
+        info!("Secret files exporter has been shut down");
+        Ok(())
+    }
+}
+
+async fn export_secret_files<V>(
+    files_source: &Arc<FilesSource>,
+    secret_refs: &[SecretVaultRef],
+    view: &V,
+) -> SecretVaultResult<()>
+where
+    V: SecretVaultView + Send + Sync,
+{
+    for secret_ref in secret_refs {
+        // The value is copied out of the view, so files are written without holding the vault store
+        let secret_value = view
+            .with_secret_by_ref(secret_ref, |secret_value, _| secret_value.clone())
+            .await?;
+
+        let files_source = files_source.clone();
+        let exported_secret_ref = secret_ref.clone();
+        let written = tokio::task::spawn_blocking(move || match secret_value {
+            Some(secret_value) => write_exported_secret_file(
+                &files_source,
+                &files_source.secret_file_name(&exported_secret_ref),
+                &secret_value,
+            ),
+            None => remove_secret_file(&files_source, &exported_secret_ref).map(|_| false),
+        })
+        .await
+        .map_err(|err| {
+            SecretVaultSystemError::create(
+                "SECRET_FILE_EXPORT",
+                &format!("Exporting secret file task has failed: {err}"),
+            )
+        })??;
+
+        if written {
+            debug!("Secret {:?} has been exported", secret_ref.key);
+        }
+    }
+
+    Ok(())
+}
+
+fn write_exported_secret_file(
+    files_source: &FilesSource,
+    secret_file_name: &str,
+    secret_value: &secret_vault_value::SecretValue,
+) -> SecretVaultResult<bool> {
+    let existing_value = std::fs::read(secret_file_name)
+        .ok()
+        .map(secret_vault_value::SecretValue::from);
+    if existing_value.as_ref() == Some(secret_value) {
+        Ok(false)
+    } else {
+        files_source
+            .write_secret_file(secret_file_name, secret_value)
+            .map(|_| true)
+    }
+}
+
+fn remove_secret_file(
+    files_source: &FilesSource,
+    secret_ref: &SecretVaultRef,
+) -> SecretVaultResult<()> {
+    let secret_file_name = files_source.secret_file_name(secret_ref);
+    match std::fs::remove_file(Path::new(&secret_file_name)) {
+        Ok(_) => {
+            debug!(
+                "Exported secret file for {:?} has been removed",
+                secret_ref.key
+            );
+            Ok(())
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(SecretVaultSystemError::create(
+            "SECRET_FILE_DELETE",
+            &format!("Unable to remove exported secret file `{secret_file_name}`: {err}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secret_vault_value::SecretValue;
+
+    #[tokio::test]
+    async fn export_secret_files_test() {
+        let export_dir = tempfile::tempdir().unwrap();
+        let secret_ref1 = SecretVaultRef::new("secret1".into());
+        let secret_ref2 = SecretVaultRef::new("secret2".into()).with_secret_version("2".into());
+
+        let mut mock_secrets_store = MockSecretsSource::new(vec![
+            (secret_ref1.clone(), SecretValue::from("test-value1")),
+            (secret_ref2.clone(), SecretValue::from("test-value2")),
+        ]);
+
+        let mut vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&secret_ref1, &secret_ref2])
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        let mut exporter = SecretVaultFilesExporter::new(
+            FilesSourceOptions::new().with_root_path(export_dir.path().into()),
+            vec![&secret_ref1, &secret_ref2],
+        )
+        .unwrap();
+        exporter
+            .start(vault.viewer(), vault.subscribe_changes())
+            .await
+            .unwrap();
+
+        let exported_file1 = export_dir.path().join("secret1");
+        let exported_file2 = export_dir.path().join("secret2_v2");
+        assert_eq!(std::fs::read(&exported_file1).unwrap(), b"test-value1");
+        assert_eq!(std::fs::read(&exported_file2).unwrap(), b"test-value2");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let file_mode = std::fs::metadata(&exported_file1)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(file_mode & 0o777, 0o400);
+        }
+
+        mock_secrets_store.add(secret_ref1.clone(), SecretValue::from("new-value1"));
+        vault
+            .remove_secret_ref(&secret_ref2.key)
+            .refresh()
+            .await
+            .unwrap();
+
+        for _ in 0..100 {
+            if !exported_file2.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(std::fs::read(&exported_file1).unwrap(), b"new-value1");
+        assert!(!exported_file2.exists());
+
+        exporter.shutdown().await.unwrap();
+        assert!(!exported_file1.exists());
+    }
+
+    #[tokio::test]
+    async fn export_compacted_secret_files_test() {
+        let export_dir = tempfile::tempdir().unwrap();
+        let secret_ref = SecretVaultRef::new("secret1".into());
+
+        let mut vault = SecretVaultBuilder::with_source(MockSecretsSource::new(vec![(
+            secret_ref.clone(),
+            SecretValue::from("test-value1"),
+        )]))
+        .with_secret_refs(vec![&secret_ref])
+        .build()
+        .unwrap();
+        vault.refresh().await.unwrap();
+
+        let mut exporter = SecretVaultFilesExporter::new(
+            FilesSourceOptions::new().with_root_path(export_dir.path().into()),
+            vec![&secret_ref],
+        )
+        .unwrap();
+        exporter
+            .start(vault.viewer(), vault.subscribe_changes())
+            .await
+            .unwrap();
+
+        let exported_file = export_dir.path().join("secret1");
+        assert!(exported_file.exists());
+
+        vault
+            .remove_secret_ref(&secret_ref.key)
+            .compact()
+            .await
+            .unwrap();
+
+        for _ in 0..100 {
+            if !exported_file.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!exported_file.exists());
+
+        exporter.shutdown().await.unwrap();
+    }
+}
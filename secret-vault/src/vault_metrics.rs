@@ -0,0 +1,236 @@
+//! Metrics recorded with the `metrics` facade when the `metrics` feature is enabled.
+//! Without the feature all functions are no-ops.
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use crate::*;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use rvstruct::ValueStruct;
+
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_REFRESH_DURATION: &str = "secret_vault_refresh_duration_seconds";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_REFRESHES: &str = "secret_vault_refreshes_total";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_SECRETS_CACHED: &str = "secret_vault_secrets_cached";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_SECRET_CACHED_TIMESTAMP: &str =
+    "secret_vault_secret_cached_timestamp_seconds";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_ENCRYPTION_DURATION: &str = "secret_vault_encryption_duration_seconds";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_CACHE_MISSES: &str = "secret_vault_cache_misses_total";
+#[cfg(feature = "metrics")]
+pub(crate) const METRIC_AUTO_REFRESH_FAILURES: &str = "secret_vault_auto_refresh_failures_total";
+
+pub(crate) fn refresh_outcome<T>(result: &SecretVaultResult<T>) -> &'static str {
+    if result.is_ok() {
+        "success"
+    } else {
+        "error"
+    }
+}
+
+/// `partial` when only some of the secrets have failed.
+pub(crate) fn partial_refresh_outcome<T>(
+    results: &HashMap<SecretVaultRef, SecretVaultResult<T>>,
+) -> &'static str {
+    let failed = results.values().filter(|result| result.is_err()).count();
+    if failed == 0 {
+        "success"
+    } else if failed == results.len() {
+        "error"
+    } else {
+        "partial"
+    }
+}
+
+pub(crate) fn record_refresh(source: &str, duration: Duration, outcome: &'static str) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::histogram!(METRIC_REFRESH_DURATION, "source" => source.to_string(), "outcome" => outcome)
+            .record(duration.as_secs_f64());
+        metrics::counter!(METRIC_REFRESHES, "source" => source.to_string(), "outcome" => outcome)
+            .increment(1);
+    }
+}
+
+pub(crate) fn record_secrets_cached(secrets_cached: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(METRIC_SECRETS_CACHED).set(secrets_cached as f64);
+}
+
+/// The age of secrets is calculated as the difference with the current time,
+/// e.g. `time() - secret_vault_secret_cached_timestamp_seconds` in Prometheus.
+pub(crate) fn record_secret_cached(metadata: &SecretMetadata) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(
+        METRIC_SECRET_CACHED_TIMESTAMP,
+        "secret_name" => metadata.key.secret_name.value().clone(),
+        "namespace" => metadata.key.namespace.as_ref().map(|namespace| namespace.value().clone()).unwrap_or_default()
+    )
+    .set(metadata.cached_at.timestamp_millis() as f64 / 1000.0);
+}
+
+/// Resets the cached timestamp of a removed secret to zero, since the facade can't remove a gauge.
+pub(crate) fn record_secret_removed(secret_vault_key: &SecretVaultKey) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(
+        METRIC_SECRET_CACHED_TIMESTAMP,
+        "secret_name" => secret_vault_key.secret_name.value().clone(),
+        "namespace" => secret_vault_key.namespace.as_ref().map(|namespace| namespace.value().clone()).unwrap_or_default()
+    )
+    .set(0.0);
+}
+
+pub(crate) fn record_encryption(operation: &'static str, duration: Duration) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!(METRIC_ENCRYPTION_DURATION, "operation" => operation)
+        .record(duration.as_secs_f64());
+}
+
+pub(crate) fn record_cache_miss() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(METRIC_CACHE_MISSES).increment(1);
+}
+
+pub(crate) fn record_auto_refresh_failure(operation: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(METRIC_AUTO_REFRESH_FAILURES, "operation" => operation).increment(1);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+    use secret_vault_value::SecretValue;
+
+    type RecordedMetric = (MetricKind, String, Vec<(String, String)>, DebugValue);
+
+    #[test]
+    fn vault_metrics_test() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let secret_ref = SecretVaultRef::new("secret1".into());
+                    let vault = SecretVaultBuilder::with_source(MockSecretsSource::new(vec![(
+                        secret_ref.clone(),
+                        SecretValue::from("test-value"),
+                    )]))
+                    .with_secret_refs(vec![&secret_ref])
+                    .build()
+                    .unwrap();
+
+                    vault.refresh().await.unwrap();
+                    vault.get_secret_by_ref(&secret_ref).await.unwrap();
+                    vault
+                        .get_secret_by_ref(&SecretVaultRef::new("unknown".into()))
+                        .await
+                        .unwrap();
+                })
+        });
+
+        let metrics: Vec<RecordedMetric> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(composite_key, _, _, value)| {
+                let (kind, key) = composite_key.into_parts();
+                let labels = key
+                    .labels()
+                    .map(|label| (label.key().to_string(), label.value().to_string()))
+                    .collect();
+                (kind, key.name().to_string(), labels, value)
+            })
+            .collect();
+
+        let find_metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|(_, metric_name, _, _)| metric_name == name)
+                .unwrap_or_else(|| panic!("Metric {name} wasn't recorded"))
+        };
+
+        let (_, _, labels, value) = find_metric(METRIC_REFRESHES);
+        assert!(labels.contains(&("source".to_string(), "MockSecretsSource".to_string())));
+        assert!(labels.contains(&("outcome".to_string(), "success".to_string())));
+        assert_eq!(value, &DebugValue::Counter(1));
+
+        assert_eq!(
+            find_metric(METRIC_SECRETS_CACHED).3,
+            DebugValue::Gauge(1.0.into())
+        );
+        assert_eq!(find_metric(METRIC_CACHE_MISSES).3, DebugValue::Counter(1));
+        assert_eq!(
+            find_metric(METRIC_REFRESH_DURATION).0,
+            MetricKind::Histogram
+        );
+        assert_eq!(
+            find_metric(METRIC_ENCRYPTION_DURATION).0,
+            MetricKind::Histogram
+        );
+
+        let (_, _, labels, _) = find_metric(METRIC_SECRET_CACHED_TIMESTAMP);
+        assert!(labels.contains(&("secret_name".to_string(), "secret1".to_string())));
+    }
+
+    #[test]
+    fn removed_secret_metrics_test() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let secret_ref1 = SecretVaultRef::new("secret1".into());
+                    let secret_ref2 = SecretVaultRef::new("secret2".into());
+                    let mut vault = SecretVaultBuilder::with_source(MockSecretsSource::new(vec![
+                        (secret_ref1.clone(), SecretValue::from("test-value1")),
+                        (secret_ref2.clone(), SecretValue::from("test-value2")),
+                    ]))
+                    .with_secret_refs(vec![&secret_ref1, &secret_ref2])
+                    .build()
+                    .unwrap();
+
+                    vault.refresh().await.unwrap();
+                    vault
+                        .remove_secret_ref(&secret_ref2.key)
+                        .refresh()
+                        .await
+                        .unwrap();
+                })
+        });
+
+        let cached_timestamps: Vec<(String, DebugValue)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter(|(composite_key, _, _, _)| {
+                composite_key.key().name() == METRIC_SECRET_CACHED_TIMESTAMP
+            })
+            .filter_map(|(composite_key, _, _, value)| {
+                composite_key
+                    .key()
+                    .labels()
+                    .find(|label| label.key() == "secret_name")
+                    .map(|label| (label.value().to_string(), value))
+            })
+            .collect();
+
+        assert!(cached_timestamps.contains(&("secret2".to_string(), DebugValue::Gauge(0.0.into()))));
+        assert!(cached_timestamps
+            .iter()
+            .any(|(secret_name, value)| secret_name == "secret1"
+                && *value != DebugValue::Gauge(0.0.into())));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::errors::*;
+use crate::*;
+
+#[derive(Debug)]
+pub enum SecretVaultRefreshOutcome {
+    /// The secret has been cached for the first time or its value has changed.
+    Updated,
+    /// The source returned the same value as the cached one.
+    Unchanged,
+    /// The secret doesn't exist in the source.
+    Missing,
+    /// Reading or storing the secret failed. The previously cached value (if any) is kept.
+    Failed(SecretVaultError),
+}
+
+/// Per secret ref results of a refresh. Successfully read secrets are stored
+/// even when other secrets fail.
+#[derive(Debug, Default)]
+pub struct SecretVaultRefreshReport {
+    pub outcomes: Vec<(SecretVaultRef, SecretVaultRefreshOutcome)>,
+}
+
+impl SecretVaultRefreshReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_outcome(&mut self, secret_ref: SecretVaultRef, outcome: SecretVaultRefreshOutcome) {
+        self.outcomes.push((secret_ref, outcome));
+    }
+
+    pub fn outcome(&self, secret_vault_key: &SecretVaultKey) -> Option<&SecretVaultRefreshOutcome> {
+        self.outcomes
+            .iter()
+            .find(|(secret_ref, _)| secret_ref.key == *secret_vault_key)
+            .map(|(_, outcome)| outcome)
+    }
+
+    pub fn updated(&self) -> Vec<&SecretVaultRef> {
+        self.filter_refs(|outcome| matches!(outcome, SecretVaultRefreshOutcome::Updated))
+    }
+
+    pub fn unchanged(&self) -> Vec<&SecretVaultRef> {
+        self.filter_refs(|outcome| matches!(outcome, SecretVaultRefreshOutcome::Unchanged))
+    }
+
+    pub fn missing(&self) -> Vec<&SecretVaultRef> {
+        self.filter_refs(|outcome| matches!(outcome, SecretVaultRefreshOutcome::Missing))
+    }
+
+    pub fn failed(&self) -> Vec<(&SecretVaultRef, &SecretVaultError)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(secret_ref, outcome)| match outcome {
+                SecretVaultRefreshOutcome::Failed(err) => Some((secret_ref, err)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// No secrets have failed and all required secrets exist.
+    pub fn is_complete(&self) -> bool {
+        self.outcomes
+            .iter()
+            .all(|(secret_ref, outcome)| match outcome {
+                SecretVaultRefreshOutcome::Updated | SecretVaultRefreshOutcome::Unchanged => true,
+                SecretVaultRefreshOutcome::Missing => !secret_ref.required,
+                SecretVaultRefreshOutcome::Failed(_) => false,
+            })
+    }
+
+    fn filter_refs<P>(&self, predicate: P) -> Vec<&SecretVaultRef>
+    where
+        P: Fn(&SecretVaultRefreshOutcome) -> bool,
+    {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| predicate(outcome))
+            .map(|(secret_ref, _)| secret_ref)
+            .collect()
+    }
+}
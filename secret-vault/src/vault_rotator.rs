@@ -0,0 +1,544 @@
+use crate::errors::*;
+use crate::*;
+use async_trait::async_trait;
+use rsb_derive::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::*;
+use tracing::*;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Builder)]
+pub struct SecretVaultRotatorOptions {
+    #[default = "\"AWSPENDING\".into()"]
+    pub pending_version: SecretVersion,
+
+    #[default = "\"AWSPREVIOUS\".into()"]
+    pub previous_version: SecretVersion,
+
+    #[default = "true"]
+    pub keep_previous_version: bool,
+
+    pub rotation_interval: Option<Duration>,
+}
+
+/// Validates a newly generated secret before it is promoted to the current version
+/// (e.g. trying to login to a database with new credentials).
+#[async_trait]
+pub trait SecretRotationValidator {
+    async fn validate_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret: &Secret,
+    ) -> SecretVaultResult<()>;
+}
+
+#[derive(Clone)]
+struct SecretRotation {
+    secret_ref: SecretVaultRef,
+    generator: Arc<dyn SecretGenerator + Send + Sync>,
+    validator: Option<Arc<dyn SecretRotationValidator + Send + Sync>>,
+}
+
+pub struct SecretVaultRotator<S, E>
+where
+    S: SecretsSource + SecretsSink + Send + Sync,
+    E: SecretVaultEncryption + Sync + Send,
+{
+    vault: Arc<SecretVault<S, E>>,
+    options: SecretVaultRotatorOptions,
+    rotations: Arc<Vec<SecretRotation>>,
+    shutdown: Arc<AtomicBool>,
+    shutdown_handle: Option<JoinHandle<()>>,
+    shutdown_writer: Option<Arc<UnboundedSender<i8>>>,
+}
+
+impl<S, E> SecretVaultRotator<S, E>
+where
+    S: SecretsSource + SecretsSink + 'static + Send + Sync,
+    E: SecretVaultEncryption + Sync + Send + 'static,
+{
+    pub fn new(vault: Arc<SecretVault<S, E>>, options: SecretVaultRotatorOptions) -> Self {
+        Self {
+            vault,
+            options,
+            rotations: Arc::new(Vec::new()),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            shutdown_handle: None,
+            shutdown_writer: None,
+        }
+    }
+
+    pub fn add_secret_rotation<G>(self, secret_ref: &SecretVaultRef, generator: G) -> Self
+    where
+        G: SecretGenerator + Send + Sync + 'static,
+    {
+        self.add_rotation(SecretRotation {
+            secret_ref: secret_ref.clone(),
+            generator: Arc::new(generator),
+            validator: None,
+        })
+    }
+
+    pub fn add_validated_secret_rotation<G, V>(
+        self,
+        secret_ref: &SecretVaultRef,
+        generator: G,
+        validator: V,
+    ) -> Self
+    where
+        G: SecretGenerator + Send + Sync + 'static,
+        V: SecretRotationValidator + Send + Sync + 'static,
+    {
+        self.add_rotation(SecretRotation {
+            secret_ref: secret_ref.clone(),
+            generator: Arc::new(generator),
+            validator: Some(Arc::new(validator)),
+        })
+    }
+
+    fn add_rotation(self, rotation: SecretRotation) -> Self {
+        let mut rotations: Vec<SecretRotation> = self.rotations.iter().cloned().collect();
+        rotations.retain(|existing| existing.secret_ref.key != rotation.secret_ref.key);
+        rotations.push(rotation);
+        Self {
+            rotations: Arc::new(rotations),
+            ..self
+        }
+    }
+
+    pub async fn rotate_secret(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<SecretMetadata> {
+        match self
+            .rotations
+            .iter()
+            .find(|rotation| rotation.secret_ref.key == secret_ref.key)
+        {
+            Some(rotation) => Self::rotate(&self.vault, &self.options, rotation).await,
+            None => Err(SecretVaultError::DataNotFoundError(
+                SecretVaultDataNotFoundError::new(
+                    SecretVaultErrorPublicGenericDetails::new("ROTATION_NOT_FOUND".into()),
+                    format!(
+                        "Secret {:?} doesn't have registered rotation",
+                        secret_ref.key
+                    ),
+                ),
+            )),
+        }
+    }
+
+    pub async fn rotate_all(&self) -> SecretVaultResult<()> {
+        for rotation in self.rotations.iter() {
+            Self::rotate(&self.vault, &self.options, rotation).await?;
+        }
+        Ok(())
+    }
+
+    async fn rotate(
+        vault: &SecretVault<S, E>,
+        options: &SecretVaultRotatorOptions,
+        rotation: &SecretRotation,
+    ) -> SecretVaultResult<SecretMetadata> {
+        let secret_ref = &rotation.secret_ref;
+        let sink = vault.source();
+
+        info!("Rotating secret: {:?}", secret_ref.key);
+
+        let stages = SecretVersionStages::new(options.pending_version.clone()).opt_previous(
+            options
+                .keep_previous_version
+                .then(|| options.previous_version.clone()),
+        );
+
+        let pending_secret = rotation.generator.generate_secret(
+            &secret_ref
+                .clone()
+                .with_secret_version(options.pending_version.clone()),
+        )?;
+        let staged_version = sink
+            .stage_secret_version(secret_ref, &pending_secret.value, &stages)
+            .await?;
+
+        if let Some(validator) = &rotation.validator {
+            if let Err(err) = validator.validate_secret(secret_ref, &pending_secret).await {
+                warn!(
+                    "New secret version for {:?} didn't pass validation: {}",
+                    secret_ref.key, err
+                );
+                if let Err(discard_err) = sink
+                    .discard_secret_version(secret_ref, staged_version.as_ref(), &stages)
+                    .await
+                {
+                    warn!(
+                        "Unable to discard pending secret version for {:?}: {}",
+                        secret_ref.key, discard_err
+                    );
+                }
+                return Err(SecretVaultError::SecretsSourceError(
+                    SecretsSourceError::new(
+                        SecretVaultErrorPublicGenericDetails::new(
+                            "SECRET_ROTATION_VALIDATION".into(),
+                        ),
+                        format!(
+                            "New secret version for {:?} didn't pass validation",
+                            secret_ref.key
+                        ),
+                    )
+                    .with_root_cause(Box::new(err)),
+                ));
+            }
+        }
+
+        let metadata = vault
+            .promote_secret_version(
+                secret_ref,
+                pending_secret.value,
+                staged_version.as_ref(),
+                &stages,
+            )
+            .await?;
+
+        info!("Secret {:?} has been rotated", secret_ref.key);
+
+        Ok(metadata)
+    }
+
+    pub async fn start(&mut self) -> SecretVaultResult<()> {
+        let rotation_interval = self.options.rotation_interval.ok_or_else(|| {
+            SecretVaultInvalidParametersError::create(
+                "rotation_interval",
+                "Rotation interval must be specified to start scheduled rotations",
+            )
+        })?;
+
+        info!(
+            "Starting SecretVault scheduled rotations: {:?}",
+            self.options
+        );
+
+        let (tx, rx): (UnboundedSender<i8>, UnboundedReceiver<i8>) =
+            tokio::sync::mpsc::unbounded_channel();
+
+        self.shutdown_writer = Some(Arc::new(tx));
+
+        self.shutdown_handle = Some(tokio::spawn(Self::scheduled_rotations(
+            self.vault.clone(),
+            self.options.clone(),
+            self.rotations.clone(),
+            rotation_interval,
+            self.shutdown.clone(),
+            rx,
+        )));
+
+        Ok(())
+    }
+
+    pub async fn shutdown(&mut self) -> SecretVaultResult<()> {
+        debug!("Shutting down secret vault rotator ...");
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        if let Some(shutdown_writer) = self.shutdown_writer.take() {
+            shutdown_writer.send(1).ok();
+        }
+        if let Some(signaller) = self.shutdown_handle.take() {
+            signaller.await.expect("The task being joined has panicked");
+        }
+        info!("Shutting down secret vault rotator has been finished...");
+        Ok(())
+    }
+
+    async fn scheduled_rotations(
+        vault: Arc<SecretVault<S, E>>,
+        options: SecretVaultRotatorOptions,
+        rotations: Arc<Vec<SecretRotation>>,
+        rotation_interval: Duration,
+        shutdown_flag: Arc<AtomicBool>,
+        mut shutdown_receiver: UnboundedReceiver<i8>,
+    ) {
+        let mut interval = interval(rotation_interval);
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_receiver.recv() => {
+                    trace!("Exiting from rotation thread...");
+                    shutdown_receiver.close();
+                    break;
+                },
+                _ = interval.tick() => {
+                    if shutdown_flag.load(Ordering::Relaxed) {
+                        trace!("Exiting from rotation thread...");
+                        shutdown_receiver.close();
+                        break;
+                    }
+                    for rotation in rotations.iter() {
+                        if let Err(err) = Self::rotate(&vault, &options, rotation).await {
+                            warn!("Scheduled secret rotation error for {:?}: {}", rotation.secret_ref.key, err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+    use crate::*;
+    use async_trait::async_trait;
+    use secret_vault_value::SecretValue;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn counter_generator(
+        counter: Arc<AtomicUsize>,
+    ) -> impl Fn(&SecretVaultRef) -> SecretVaultResult<Secret> {
+        move |secret_ref| {
+            Ok(Secret::new(
+                SecretValue::from(format!(
+                    "generated-{}",
+                    counter.fetch_add(1, Ordering::Relaxed)
+                )),
+                SecretMetadata::create_from_ref(secret_ref),
+            ))
+        }
+    }
+
+    struct RejectAllValidator;
+
+    #[async_trait]
+    impl SecretRotationValidator for RejectAllValidator {
+        async fn validate_secret(
+            &self,
+            _secret_ref: &SecretVaultRef,
+            _secret: &Secret,
+        ) -> SecretVaultResult<()> {
+            Err(SecretVaultInvalidParametersError::create(
+                "secret",
+                "Rejected by test validator",
+            ))
+        }
+    }
+
+    /// Checks that the pending value is staged but not current while it is validated.
+    struct StagedValueValidator {
+        source: MockSecretsSource,
+        current_value: SecretValue,
+    }
+
+    #[async_trait]
+    impl SecretRotationValidator for StagedValueValidator {
+        async fn validate_secret(
+            &self,
+            secret_ref: &SecretVaultRef,
+            secret: &Secret,
+        ) -> SecretVaultResult<()> {
+            assert_eq!(
+                self.source.get(secret_ref),
+                Some(self.current_value.clone())
+            );
+            assert_eq!(
+                self.source
+                    .get(&secret_ref.clone().with_secret_version("AWSPENDING".into())),
+                Some(secret.value.clone())
+            );
+            Ok(())
+        }
+    }
+
+    /// A sink without staging support (like GCP) that relies on the default staging methods.
+    #[derive(Clone)]
+    struct UnstagedSecretsSource(MockSecretsSource);
+
+    #[async_trait]
+    impl SecretsSource for UnstagedSecretsSource {
+        fn name(&self) -> String {
+            "UnstagedSecretsSource".to_string()
+        }
+
+        async fn get_secrets(
+            &self,
+            references: &[SecretVaultRef],
+        ) -> SecretVaultResult<HashMap<SecretVaultRef, Secret>> {
+            self.0.get_secrets(references).await
+        }
+    }
+
+    #[async_trait]
+    impl SecretsSink for UnstagedSecretsSource {
+        async fn put_secret(
+            &self,
+            secret_ref: &SecretVaultRef,
+            secret_value: &SecretValue,
+        ) -> SecretVaultResult<SecretMetadata> {
+            self.0.put_secret(secret_ref, secret_value).await
+        }
+
+        async fn add_secret_version(
+            &self,
+            secret_ref: &SecretVaultRef,
+            secret_value: &SecretValue,
+        ) -> SecretVaultResult<SecretMetadata> {
+            self.0.add_secret_version(secret_ref, secret_value).await
+        }
+
+        async fn disable_secret_version(
+            &self,
+            secret_ref: &SecretVaultRef,
+        ) -> SecretVaultResult<()> {
+            self.0.disable_secret_version(secret_ref).await
+        }
+
+        async fn delete_secret(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<()> {
+            self.0.delete_secret(secret_ref).await
+        }
+    }
+
+    #[tokio::test]
+    async fn rotate_secret_test() {
+        let secret_ref = SecretVaultRef::new("test-rotated-secret".into());
+        let mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref.clone(), SecretValue::from("initial"))]);
+
+        let vault = Arc::new(
+            SecretVaultBuilder::with_source(mock_secrets_store.clone())
+                .with_secret_refs(vec![&secret_ref])
+                .build()
+                .unwrap(),
+        );
+        vault.refresh().await.unwrap();
+
+        let rotator = SecretVaultRotator::new(vault.clone(), SecretVaultRotatorOptions::new())
+            .add_validated_secret_rotation(
+                &secret_ref,
+                counter_generator(Arc::new(AtomicUsize::new(1))),
+                StagedValueValidator {
+                    source: mock_secrets_store.clone(),
+                    current_value: SecretValue::from("initial"),
+                },
+            );
+
+        rotator.rotate_secret(&secret_ref).await.unwrap();
+
+        assert_eq!(
+            vault
+                .require_secret_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .value,
+            SecretValue::from("generated-1")
+        );
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref),
+            Some(SecretValue::from("generated-1"))
+        );
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref.clone().with_secret_version("AWSPREVIOUS".into())),
+            Some(SecretValue::from("initial"))
+        );
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref.clone().with_secret_version("AWSPENDING".into())),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn rotate_secret_validation_failed_test() {
+        let secret_ref = SecretVaultRef::new("test-rotated-secret".into());
+        let mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref.clone(), SecretValue::from("initial"))]);
+
+        let vault = Arc::new(
+            SecretVaultBuilder::with_source(mock_secrets_store.clone())
+                .with_secret_refs(vec![&secret_ref])
+                .build()
+                .unwrap(),
+        );
+        vault.refresh().await.unwrap();
+
+        let rotator = SecretVaultRotator::new(vault.clone(), SecretVaultRotatorOptions::new())
+            .add_validated_secret_rotation(
+                &secret_ref,
+                counter_generator(Arc::new(AtomicUsize::new(1))),
+                RejectAllValidator,
+            );
+
+        rotator
+            .rotate_secret(&secret_ref)
+            .await
+            .expect_err("Rotation must fail validation");
+
+        assert_eq!(
+            vault
+                .require_secret_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .value,
+            SecretValue::from("initial")
+        );
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref),
+            Some(SecretValue::from("initial"))
+        );
+        assert_eq!(mock_secrets_store.keys(), vec![secret_ref]);
+    }
+
+    #[tokio::test]
+    async fn rotate_unstaged_secret_test() {
+        let secret_ref = SecretVaultRef::new("test-rotated-secret".into());
+        let mock_secrets_store =
+            MockSecretsSource::new(vec![(secret_ref.clone(), SecretValue::from("initial"))]);
+
+        let vault = Arc::new(
+            SecretVaultBuilder::with_source(UnstagedSecretsSource(mock_secrets_store.clone()))
+                .with_secret_refs(vec![&secret_ref])
+                .build()
+                .unwrap(),
+        );
+        vault.refresh().await.unwrap();
+
+        let rejecting_rotator =
+            SecretVaultRotator::new(vault.clone(), SecretVaultRotatorOptions::new())
+                .add_validated_secret_rotation(
+                    &secret_ref,
+                    counter_generator(Arc::new(AtomicUsize::new(1))),
+                    RejectAllValidator,
+                );
+        rejecting_rotator
+            .rotate_secret(&secret_ref)
+            .await
+            .expect_err("Rotation must fail validation");
+        assert_eq!(mock_secrets_store.keys(), vec![secret_ref.clone()]);
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref),
+            Some(SecretValue::from("initial"))
+        );
+
+        let rotator = SecretVaultRotator::new(vault.clone(), SecretVaultRotatorOptions::new())
+            .add_secret_rotation(
+                &secret_ref,
+                counter_generator(Arc::new(AtomicUsize::new(1))),
+            );
+        rotator.rotate_secret(&secret_ref).await.unwrap();
+
+        assert_eq!(mock_secrets_store.keys(), vec![secret_ref.clone()]);
+        assert_eq!(
+            mock_secrets_store.get(&secret_ref),
+            Some(SecretValue::from("generated-1"))
+        );
+        assert_eq!(
+            vault
+                .require_secret_by_ref(&secret_ref)
+                .await
+                .unwrap()
+                .value,
+            SecretValue::from("generated-1")
+        );
+    }
+}
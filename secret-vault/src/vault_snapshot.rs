@@ -1,5 +1,7 @@
 use crate::errors::*;
 use crate::*;
+use secret_vault_value::SecretValue;
+use std::sync::Arc;
 
 pub trait SecretVaultSnapshot {
     fn get_secret(&self, secret_name: &SecretName) -> SecretVaultResult<Option<Secret>> {
@@ -43,6 +45,81 @@ pub trait SecretVaultSnapshot {
     }
 
     fn get_secret_by_ref(&self, secret_ref: &SecretVaultRef) -> SecretVaultResult<Option<Secret>>;
+
+    /// Provides access to a secret without cloning it.
+    fn with_secret<F, R>(&self, secret_name: &SecretName, f: F) -> SecretVaultResult<Option<R>>
+    where
+        Self: Sized,
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        self.with_secret_by_ref(&SecretVaultRef::new(secret_name.clone()), f)
+    }
+
+    fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        Self: Sized,
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        Ok(self
+            .get_secret_by_ref(secret_ref)?
+            .map(|secret| f(&secret.value, &secret.metadata)))
+    }
+
+    /// Decodes a secret into a typed value using `SecretDecode`.
+    fn get_secret_as<T: SecretDecode>(
+        &self,
+        secret_name: &SecretName,
+    ) -> SecretVaultResult<Option<Arc<T>>>
+    where
+        Self: Sized,
+    {
+        self.get_secret_as_by_ref(&SecretVaultRef::new(secret_name.clone()))
+    }
+
+    fn require_secret_as<T: SecretDecode>(
+        &self,
+        secret_name: &SecretName,
+    ) -> SecretVaultResult<Arc<T>>
+    where
+        Self: Sized,
+    {
+        self.require_secret_as_by_ref(&SecretVaultRef::new(secret_name.clone()))
+    }
+
+    fn require_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Arc<T>>
+    where
+        Self: Sized,
+    {
+        match self.get_secret_as_by_ref(secret_ref)? {
+            Some(decoded) => Ok(decoded),
+            None => Err(SecretVaultError::DataNotFoundError(
+                SecretVaultDataNotFoundError::new(
+                    SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
+                    format!("Secret {secret_ref:?} doesn't exist in vault but was required"),
+                ),
+            )),
+        }
+    }
+
+    fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>>
+    where
+        Self: Sized,
+    {
+        self.with_secret_by_ref(secret_ref, |secret_value, _| {
+            decode_secret_value::<T>(&secret_ref.key, secret_value).map(Arc::new)
+        })?
+        .transpose()
+    }
 }
 
 pub trait SecretVaultSnapshotBuilder<SN>
@@ -1,9 +1,20 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
 
 use crate::common_types::*;
 use crate::encryption::*;
+use crate::secret_decode::*;
+use crate::secret_fingerprint::*;
+use crate::vault_audit::*;
+use crate::vault_metrics::*;
+use crate::vault_tracing::*;
 use crate::SecretVaultResult;
+use secret_vault_value::SecretValue;
+use tracing::*;
 
 #[cfg(not(feature = "ahash"))]
 type SecretVaultMap = std::collections::HashMap<SecretVaultKey, SecretVaultStoreValue>;
@@ -11,8 +22,29 @@ type SecretVaultMap = std::collections::HashMap<SecretVaultKey, SecretVaultStore
 #[cfg(feature = "ahash")]
 type SecretVaultMap = ahash::AHashMap<SecretVaultKey, SecretVaultStoreValue>;
 
+/// The cached timestamp metric is labelled without versions,
+/// so it is reset only when no other version of the secret is cached.
+fn record_removed_secret(secrets: &SecretVaultMap, removed_key: &SecretVaultKey) {
+    if !secrets.keys().any(|key| {
+        key.secret_name == removed_key.secret_name && key.namespace == removed_key.namespace
+    }) {
+        record_secret_removed(removed_key);
+    }
+}
+
 #[derive(Debug)]
 pub struct SecretVaultStoreValue {
+    pub data: EncryptedSecretValue,
+    /// `None` without the `ring-aead-encryption` feature.
+    pub fingerprint: Option<SecretFingerprint>,
+    pub metadata: SecretMetadata,
+    /// When the value has been read from the source last time.
+    pub last_checked_at: DateTime<Utc>,
+    pub previous_versions: VecDeque<SecretVaultStoreVersion>,
+}
+
+#[derive(Debug)]
+pub struct SecretVaultStoreVersion {
     pub data: EncryptedSecretValue,
     pub metadata: SecretMetadata,
 }
@@ -24,6 +56,11 @@ where
 {
     secrets: Arc<RwLock<SecretVaultMap>>,
     encrypter: E,
+    retained_versions: usize,
+    decoded_cache: SecretDecodedCache,
+    auditor: Option<SecretVaultAuditor>,
+    fingerprinter: SecretFingerprinter,
+    revision: AtomicU64,
 }
 
 impl<E> SecretVaultStore<E>
@@ -34,29 +71,145 @@ where
         Self {
             secrets: Arc::new(RwLock::new(SecretVaultMap::new())),
             encrypter,
+            retained_versions: 0,
+            decoded_cache: SecretDecodedCache::default(),
+            auditor: None,
+            fingerprinter: SecretFingerprinter::new(),
+            revision: AtomicU64::new(0),
         }
     }
 
-    pub async fn insert(
+    pub(crate) fn with_auditor(self, auditor: Option<SecretVaultAuditor>) -> Self {
+        Self { auditor, ..self }
+    }
+
+    async fn encrypt_value(
         &self,
-        secret_ref: SecretVaultRef,
-        secret: &Secret,
-    ) -> SecretVaultResult<()> {
-        let encrypted_secret_value = self
-            .encrypter
-            .encrypt_value(&secret_ref.key, &secret.value)
-            .await?;
+        secret_vault_key: &SecretVaultKey,
+        secret_value: &SecretValue,
+    ) -> SecretVaultResult<EncryptedSecretValue> {
+        let started_at = Instant::now();
+        let result = traced(
+            encryption_span("encrypt", secret_vault_key),
+            self.encrypter.encrypt_value(secret_vault_key, secret_value),
+        )
+        .await;
+        record_encryption("encrypt", started_at.elapsed());
+        result
+    }
 
-        let mut secrets_write = self.secrets.write().await;
-        secrets_write.insert(
-            secret_ref.key,
-            SecretVaultStoreValue {
-                data: encrypted_secret_value,
-                metadata: secret.metadata.clone(),
-            },
-        );
+    async fn decrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        encrypted_value: &EncryptedSecretValue,
+    ) -> SecretVaultResult<SecretValue> {
+        let started_at = Instant::now();
+        let result = traced(
+            encryption_span("decrypt", secret_vault_key),
+            self.encrypter
+                .decrypt_value(secret_vault_key, encrypted_value),
+        )
+        .await;
+        record_encryption("decrypt", started_at.elapsed());
+        result
+    }
 
-        Ok(())
+    pub fn audit_access(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        principal: Option<&SecretVaultPrincipal>,
+        hit: bool,
+    ) {
+        if !hit {
+            record_cache_miss();
+        }
+        if let Some(auditor) = &self.auditor {
+            auditor.audit_access(secret_vault_key, principal, hit);
+        }
+    }
+
+    pub fn audit_access_denied(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        principal: Option<&SecretVaultPrincipal>,
+    ) {
+        if let Some(auditor) = &self.auditor {
+            auditor.audit(
+                &SecretVaultAuditEvent::new(SecretVaultAuditEventKind::AccessDenied)
+                    .with_key(secret_vault_key.clone())
+                    .opt_principal(principal.cloned()),
+            );
+        }
+    }
+
+    pub fn audit_mutation(
+        &self,
+        kind: SecretVaultAuditEventKind,
+        secret_vault_key: Option<&SecretVaultKey>,
+        version: Option<&SecretVersion>,
+    ) {
+        if let Some(auditor) = &self.auditor {
+            auditor.audit(
+                &SecretVaultAuditEvent::new(kind)
+                    .opt_key(secret_vault_key.cloned())
+                    .opt_version(version.cloned()),
+            );
+        }
+    }
+
+    pub fn with_retained_versions(self, retained_versions: usize) -> Self {
+        Self {
+            retained_versions,
+            ..self
+        }
+    }
+
+    /// Incremented every time cached secrets are changed.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
+    }
+
+    pub async fn get_metadata(&self, secret_vault_key: &SecretVaultKey) -> Option<SecretMetadata> {
+        self.secrets
+            .read()
+            .await
+            .get(secret_vault_key)
+            .map(|stored_value| stored_value.metadata.clone())
+    }
+
+    pub async fn get_last_checked_at(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+    ) -> Option<DateTime<Utc>> {
+        self.secrets
+            .read()
+            .await
+            .get(secret_vault_key)
+            .map(|stored_value| stored_value.last_checked_at)
+    }
+
+    /// Returns metadata of the current version first, followed by the retained previous versions,
+    /// without decrypting them.
+    pub async fn get_versions_metadata(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+    ) -> Vec<SecretMetadata> {
+        self.secrets
+            .read()
+            .await
+            .get(secret_vault_key)
+            .map(|stored_value| {
+                std::iter::once(&stored_value.metadata)
+                    .chain(
+                        stored_value
+                            .previous_versions
+                            .iter()
+                            .map(|stored_version| &stored_version.metadata),
+                    )
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub async fn get_secret(
@@ -68,7 +221,6 @@ where
         match secrets_read.get(secret_vault_key) {
             Some(stored_value) => {
                 let secret_value = self
-                    .encrypter
                     .decrypt_value(secret_vault_key, &stored_value.data)
                     .await?;
                 Ok(Some(Secret::new(
@@ -80,9 +232,100 @@ where
         }
     }
 
+    /// Decrypts a secret into a temporary buffer that is zeroized after `f` returns.
+    /// Metadata is borrowed from the store without cloning.
+    pub async fn with_secret<F, R>(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R,
+    {
+        let secrets_read = self.secrets.read().await;
+
+        match secrets_read.get(secret_vault_key) {
+            Some(stored_value) => {
+                let secret_value = self
+                    .decrypt_value(secret_vault_key, &stored_value.data)
+                    .await?;
+                Ok(Some(f(&secret_value, &stored_value.metadata)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes a secret into a typed value.
+    /// Decoded values are cached until the secret is updated only without encryption,
+    /// so the cache doesn't keep plain text values of encrypted secrets.
+    pub async fn get_secret_as<T: SecretDecode>(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+    ) -> SecretVaultResult<Option<Arc<T>>> {
+        let secrets_read = self.secrets.read().await;
+
+        match secrets_read.get(secret_vault_key) {
+            Some(stored_value) if self.encrypter.stores_plaintext() => {
+                match self.decoded_cache.get::<T>(secret_vault_key) {
+                    Some(decoded) => Ok(Some(decoded)),
+                    None => {
+                        let secret_value = self
+                            .decrypt_value(secret_vault_key, &stored_value.data)
+                            .await?;
+                        Ok(Some(
+                            self.decoded_cache
+                                .get_or_decode::<T>(secret_vault_key, &secret_value)?,
+                        ))
+                    }
+                }
+            }
+            Some(stored_value) => {
+                let secret_value = self
+                    .decrypt_value(secret_vault_key, &stored_value.data)
+                    .await?;
+                Ok(Some(Arc::new(decode_secret_value::<T>(
+                    secret_vault_key,
+                    &secret_value,
+                )?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_secret_versions(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+    ) -> SecretVaultResult<Vec<Secret>> {
+        let secrets_read = self.secrets.read().await;
+
+        match secrets_read.get(secret_vault_key) {
+            Some(stored_value) => {
+                let mut secrets = Vec::with_capacity(stored_value.previous_versions.len() + 1);
+                secrets.push(Secret::new(
+                    self.decrypt_value(secret_vault_key, &stored_value.data)
+                        .await?,
+                    stored_value.metadata.clone(),
+                ));
+                for stored_version in stored_value.previous_versions.iter() {
+                    secrets.push(Secret::new(
+                        self.decrypt_value(secret_vault_key, &stored_version.data)
+                            .await?,
+                        stored_version.metadata.clone(),
+                    ));
+                }
+                Ok(secrets)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
     pub async fn remove(&self, secret_vault_key: &SecretVaultKey) -> SecretVaultResult<()> {
         let mut secrets_write = self.secrets.write().await;
-        secrets_write.remove(secret_vault_key);
+        if secrets_write.remove(secret_vault_key).is_some() {
+            self.revision.fetch_add(1, Ordering::Relaxed);
+            record_removed_secret(&secrets_write, secret_vault_key);
+        }
+        self.decoded_cache.invalidate(secret_vault_key);
         Ok(())
     }
 
@@ -101,8 +344,24 @@ where
             .cloned()
             .collect();
 
+        let mut removed = Vec::with_capacity(to_remove.len());
         for key in to_remove {
-            secrets_write.remove(&key);
+            if let Some(removed_value) = secrets_write.remove(&key) {
+                self.revision.fetch_add(1, Ordering::Relaxed);
+                record_removed_secret(&secrets_write, &key);
+                removed.push((key, removed_value.metadata.version));
+            }
+        }
+        self.decoded_cache
+            .retain(|key| secrets_write.contains_key(key));
+        drop(secrets_write);
+
+        for (key, version) in removed {
+            self.audit_mutation(
+                SecretVaultAuditEventKind::Compact,
+                Some(&key),
+                version.as_ref(),
+            );
         }
 
         Ok(())
@@ -130,3 +389,466 @@ where
         secrets_read.len()
     }
 }
+
+impl<E> SecretVaultStore<E>
+where
+    E: SecretVaultEncryption + Sync + Send,
+{
+    /// Stores the secret and returns `false` when the value is the same as the cached one.
+    /// Unchanged values aren't re-encrypted: only metadata is updated, keeping `cached_at`
+    /// and updating the last checked time.
+    pub async fn insert(
+        &self,
+        secret_ref: SecretVaultRef,
+        secret: &Secret,
+    ) -> SecretVaultResult<bool> {
+        let span = store_span("insert", &secret_ref.key);
+        traced(span, self.insert_secret(secret_ref, secret)).await
+    }
+
+    async fn insert_secret(
+        &self,
+        secret_ref: SecretVaultRef,
+        secret: &Secret,
+    ) -> SecretVaultResult<bool> {
+        let fingerprint = self.fingerprinter.fingerprint(&secret.value)?;
+
+        // The value is encrypted under the same write guard, so an encryption key rotation
+        // can't retire the key in between
+        let mut secrets_write = self.secrets.write().await;
+
+        if let Some(existing_value) = secrets_write.get_mut(&secret_ref.key) {
+            let unchanged = match &existing_value.fingerprint {
+                Some(existing_fingerprint) => self
+                    .fingerprinter
+                    .matches(existing_fingerprint, &secret.value)?,
+                None => {
+                    self.decrypt_value(&secret_ref.key, &existing_value.data)
+                        .await?
+                        == secret.value
+                }
+            };
+            if unchanged {
+                existing_value.metadata = SecretMetadata {
+                    cached_at: existing_value.metadata.cached_at,
+                    ..secret.metadata.clone()
+                };
+                existing_value.last_checked_at = Utc::now();
+                trace!("Secret {:?} hasn't changed", secret_ref.key);
+                return Ok(false);
+            }
+        }
+
+        let encrypted_secret_value = self.encrypt_value(&secret_ref.key, &secret.value).await?;
+
+        self.decoded_cache.invalidate(&secret_ref.key);
+
+        let previous_versions = match secrets_write.remove(&secret_ref.key) {
+            Some(existing_value) if self.retained_versions > 0 => {
+                let mut previous_versions = existing_value.previous_versions;
+                previous_versions.push_front(SecretVaultStoreVersion {
+                    data: existing_value.data,
+                    metadata: existing_value.metadata,
+                });
+                previous_versions.truncate(self.retained_versions);
+                previous_versions
+            }
+            _ => VecDeque::new(),
+        };
+
+        record_secret_cached(&secret.metadata);
+
+        secrets_write.insert(
+            secret_ref.key.clone(),
+            SecretVaultStoreValue {
+                data: encrypted_secret_value,
+                fingerprint,
+                metadata: secret.metadata.clone(),
+                last_checked_at: Utc::now(),
+                previous_versions,
+            },
+        );
+        self.revision.fetch_add(1, Ordering::Relaxed);
+
+        let reencrypted = if self.encrypter.requires_reencryption().await {
+            debug!("Vault encryption key has been rotated automatically, re-encrypting secrets");
+            self.reencrypt_secrets(&mut secrets_write).await
+        } else {
+            Ok(())
+        };
+        drop(secrets_write);
+
+        // Sinks are called without holding the write guard to avoid blocking readers
+        self.audit_mutation(
+            SecretVaultAuditEventKind::Insert,
+            Some(&secret_ref.key),
+            secret.metadata.version.as_ref(),
+        );
+        reencrypted?;
+
+        Ok(true)
+    }
+
+    pub async fn rotate_encryption_key(&self) -> SecretVaultResult<bool> {
+        let mut secrets_write = self.secrets.write().await;
+
+        if !self.encrypter.rotate_key().await? {
+            return Ok(false);
+        }
+
+        self.reencrypt_secrets(&mut secrets_write).await?;
+
+        Ok(true)
+    }
+}
+
+impl<E> SecretVaultStore<E>
+where
+    E: SecretVaultEncryption + Sync + Send,
+{
+    /// Re-encrypts all values with the current key and retires the keys rotated before it.
+    /// The key can be rotated automatically while values are re-encrypted,
+    /// so keys used by the already re-encrypted values are kept.
+    async fn reencrypt_secrets(&self, secrets: &mut SecretVaultMap) -> SecretVaultResult<()> {
+        let current_key_id = self.encrypter.current_key_id().await;
+        for (secret_vault_key, stored_value) in secrets.iter_mut() {
+            stored_value.data = self
+                .reencrypt_value(secret_vault_key, &stored_value.data)
+                .await?;
+            for stored_version in stored_value.previous_versions.iter_mut() {
+                stored_version.data = self
+                    .reencrypt_value(secret_vault_key, &stored_version.data)
+                    .await?;
+            }
+        }
+        match current_key_id {
+            Some(key_id) => self.encrypter.retire_keys_before(key_id).await,
+            None => self.encrypter.retire_previous_keys().await,
+        }
+    }
+
+    async fn reencrypt_value(
+        &self,
+        secret_vault_key: &SecretVaultKey,
+        encrypted_value: &EncryptedSecretValue,
+    ) -> SecretVaultResult<EncryptedSecretValue> {
+        let secret_value = self
+            .decrypt_value(secret_vault_key, encrypted_value)
+            .await?;
+        self.encrypt_value(secret_vault_key, &secret_value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecretVaultRef;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct DecryptCountingEncryption {
+        decrypted: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SecretVaultEncryption for DecryptCountingEncryption {
+        async fn encrypt_value(
+            &self,
+            secret_vault_key: &SecretVaultKey,
+            secret_value: &SecretValue,
+        ) -> SecretVaultResult<EncryptedSecretValue> {
+            SecretVaultNoEncryption
+                .encrypt_value(secret_vault_key, secret_value)
+                .await
+        }
+
+        async fn decrypt_value(
+            &self,
+            secret_vault_key: &SecretVaultKey,
+            encrypted_secret_value: &EncryptedSecretValue,
+        ) -> SecretVaultResult<SecretValue> {
+            self.decrypted.fetch_add(1, Ordering::Relaxed);
+            SecretVaultNoEncryption
+                .decrypt_value(secret_vault_key, encrypted_secret_value)
+                .await
+        }
+    }
+
+    /// Tags values with the key epoch and rejects values of retired epochs.
+    /// Encryption is slow to widen the window for concurrent rotations.
+    #[derive(Default)]
+    struct EpochEncryption {
+        epochs: std::sync::Mutex<(u8, Vec<u8>)>,
+    }
+
+    #[async_trait]
+    impl SecretVaultEncryption for EpochEncryption {
+        async fn encrypt_value(
+            &self,
+            _secret_vault_key: &SecretVaultKey,
+            secret_value: &SecretValue,
+        ) -> SecretVaultResult<EncryptedSecretValue> {
+            let epoch = self.epochs.lock().unwrap().0;
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            let mut data = vec![epoch];
+            data.extend_from_slice(secret_value.as_sensitive_bytes());
+            Ok(EncryptedSecretValue::from(data))
+        }
+
+        async fn decrypt_value(
+            &self,
+            secret_vault_key: &SecretVaultKey,
+            encrypted_secret_value: &EncryptedSecretValue,
+        ) -> SecretVaultResult<SecretValue> {
+            let (epoch, data) = encrypted_secret_value.0.split_first().unwrap();
+            let (current_epoch, previous_epochs) = &*self.epochs.lock().unwrap();
+            if epoch == current_epoch || previous_epochs.contains(epoch) {
+                Ok(SecretValue::from(data.to_vec()))
+            } else {
+                Err(crate::errors::SecretVaultEncryptionError::create(
+                    "RETIRED_KEY",
+                    &format!("Secret {secret_vault_key:?} is encrypted with a retired key"),
+                ))
+            }
+        }
+
+        async fn rotate_key(&self) -> SecretVaultResult<bool> {
+            let (current_epoch, previous_epochs) = &mut *self.epochs.lock().unwrap();
+            previous_epochs.push(*current_epoch);
+            *current_epoch += 1;
+            Ok(true)
+        }
+
+        async fn retire_previous_keys(&self) -> SecretVaultResult<()> {
+            self.epochs.lock().unwrap().1.clear();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_insert_and_key_rotation_test() {
+        let store = Arc::new(SecretVaultStore::new(EpochEncryption::default()));
+        let secret_refs: Vec<SecretVaultRef> = (0..5)
+            .map(|idx| SecretVaultRef::new(format!("test-secret-{idx}").into()))
+            .collect();
+
+        for round in 0..3 {
+            let inserts = secret_refs.iter().map(|secret_ref| {
+                let store = store.clone();
+                let secret_ref = secret_ref.clone();
+                tokio::spawn(async move {
+                    store
+                        .insert(
+                            secret_ref.clone(),
+                            &Secret::new(
+                                SecretValue::from(format!("value-{round}")),
+                                SecretMetadata::create_from_ref(&secret_ref),
+                            ),
+                        )
+                        .await
+                })
+            });
+            let inserts: Vec<_> = inserts.collect();
+            tokio::task::yield_now().await;
+            assert!(store.rotate_encryption_key().await.unwrap());
+
+            for insert in inserts {
+                insert.await.unwrap().unwrap();
+            }
+        }
+
+        for secret_ref in secret_refs.iter() {
+            assert_eq!(
+                store
+                    .get_secret(&secret_ref.key)
+                    .await
+                    .unwrap()
+                    .map(|secret| secret.value),
+                Some(SecretValue::from("value-2"))
+            );
+        }
+    }
+
+    #[cfg(feature = "ring-aead-encryption")]
+    #[tokio::test]
+    async fn automatic_rekey_reencrypts_secrets_test() {
+        let store = SecretVaultStore::new(
+            crate::ring_encryption::SecretVaultRingAeadEncryption::new()
+                .unwrap()
+                .with_max_encryptions_per_key(8),
+        )
+        .with_retained_versions(1);
+        let secret_refs: Vec<SecretVaultRef> = (0..3)
+            .map(|idx| SecretVaultRef::new(format!("test-secret-{idx}").into()))
+            .collect();
+
+        for round in 0..3 {
+            for secret_ref in secret_refs.iter() {
+                store
+                    .insert(
+                        secret_ref.clone(),
+                        &Secret::new(
+                            SecretValue::from(format!("value-{round}")),
+                            SecretMetadata::create_from_ref(secret_ref),
+                        ),
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+
+        for secret_ref in secret_refs.iter() {
+            assert_eq!(
+                store
+                    .get_secret_versions(&secret_ref.key)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|secret| secret.value)
+                    .collect::<Vec<_>>(),
+                vec![SecretValue::from("value-2"), SecretValue::from("value-1")]
+            );
+        }
+    }
+
+    #[cfg(feature = "ring-aead-encryption")]
+    #[tokio::test]
+    async fn rekey_during_reencryption_test() {
+        let store = SecretVaultStore::new(
+            crate::ring_encryption::SecretVaultRingAeadEncryption::new()
+                .unwrap()
+                .with_max_encryptions_per_key(2),
+        )
+        .with_retained_versions(1);
+        let secret_refs: Vec<SecretVaultRef> = (0..5)
+            .map(|idx| SecretVaultRef::new(format!("test-secret-{idx}").into()))
+            .collect();
+
+        for round in 0..3 {
+            for secret_ref in secret_refs.iter() {
+                store
+                    .insert(
+                        secret_ref.clone(),
+                        &Secret::new(
+                            SecretValue::from(format!("value-{round}")),
+                            SecretMetadata::create_from_ref(secret_ref),
+                        ),
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+        store.rotate_encryption_key().await.unwrap();
+
+        for secret_ref in secret_refs.iter() {
+            assert_eq!(
+                store
+                    .get_secret_versions(&secret_ref.key)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|secret| secret.value)
+                    .collect::<Vec<_>>(),
+                vec![SecretValue::from("value-2"), SecretValue::from("value-1")]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn versions_metadata_test() {
+        let store =
+            SecretVaultStore::new(DecryptCountingEncryption::default()).with_retained_versions(2);
+        let secret_ref = SecretVaultRef::new("test-secret".into());
+
+        for version in ["1", "2", "3"] {
+            store
+                .insert(
+                    secret_ref.clone(),
+                    &Secret::new(
+                        SecretValue::from(format!("value{version}")),
+                        SecretMetadata::create_from_ref(&secret_ref).with_version(version.into()),
+                    ),
+                )
+                .await
+                .unwrap();
+        }
+        // Without fingerprints inserts decrypt cached values to compare them
+        let decrypted_by_inserts = store.encrypter.decrypted.load(Ordering::Relaxed);
+
+        assert_eq!(
+            store
+                .get_versions_metadata(&secret_ref.key)
+                .await
+                .into_iter()
+                .map(|metadata| metadata.version)
+                .collect::<Vec<_>>(),
+            vec![Some("3".into()), Some("2".into()), Some("1".into())]
+        );
+        assert_eq!(
+            store
+                .get_metadata(&secret_ref.key)
+                .await
+                .map(|metadata| metadata.version),
+            Some(Some("3".into()))
+        );
+        assert!(store
+            .get_versions_metadata(&SecretVaultKey::new("unknown".into()))
+            .await
+            .is_empty());
+        assert_eq!(
+            store.encrypter.decrypted.load(Ordering::Relaxed),
+            decrypted_by_inserts
+        );
+    }
+
+    #[tokio::test]
+    async fn decoded_values_not_cached_with_encryption_test() {
+        let store = SecretVaultStore::new(DecryptCountingEncryption::default());
+        let secret_ref = SecretVaultRef::new("port".into());
+        store
+            .insert(
+                secret_ref.clone(),
+                &Secret::new(
+                    SecretValue::from("8080"),
+                    SecretMetadata::create_from_ref(&secret_ref),
+                ),
+            )
+            .await
+            .unwrap();
+
+        let port = store.get_secret_as::<u16>(&secret_ref.key).await.unwrap();
+        assert_eq!(port.as_deref(), Some(&8080));
+        assert_eq!(
+            store.get_secret_as::<u16>(&secret_ref.key).await.unwrap(),
+            port
+        );
+        assert_eq!(store.encrypter.decrypted.load(Ordering::Relaxed), 2);
+        assert!(store.decoded_cache.get::<u16>(&secret_ref.key).is_none());
+    }
+
+    #[cfg(feature = "memlock")]
+    #[tokio::test]
+    async fn decrypted_values_memory_locked_test() {
+        let store = SecretVaultStore::new(SecretVaultNoEncryption);
+        let secret_ref = SecretVaultRef::new("test-secret".into());
+        store
+            .insert(
+                secret_ref.clone(),
+                &Secret::new(
+                    SecretValue::from("test-value"),
+                    SecretMetadata::create_from_ref(&secret_ref),
+                ),
+            )
+            .await
+            .unwrap();
+
+        let failures = secret_vault_value::locked_memory_failures();
+        let secret = store.get_secret(&secret_ref.key).await.unwrap().unwrap();
+        // Values are still available if locking isn't permitted, but each failure is counted
+        assert!(
+            secret.value.is_memory_locked()
+                || secret_vault_value::locked_memory_failures() > failures
+        );
+    }
+}
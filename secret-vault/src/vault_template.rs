@@ -0,0 +1,349 @@
+use crate::errors::*;
+use crate::*;
+use secret_vault_value::SecretValue;
+use std::path::Path;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::*;
+use zeroize::Zeroizing;
+
+const SECRET_PLACEHOLDER_PREFIX: &str = "${secret:";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum SecretVaultTemplatePart {
+    Text(String),
+    Secret {
+        secret_ref: SecretVaultRef,
+        field: Option<String>,
+    },
+}
+
+/// Templates with secret placeholders resolved through a `SecretVaultView`:
+///
+/// - `${secret:db-password}` - the secret value;
+/// - `${secret:aws/api-key@AWSCURRENT}` - a secret from a namespace with a version;
+/// - `${secret:aws/api-key#field}` - a field of a JSON object secret (requires the `serde` feature);
+/// - `$${` - escaped `${`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SecretVaultTemplate {
+    parts: Vec<SecretVaultTemplatePart>,
+}
+
+impl SecretVaultTemplate {
+    pub fn parse(template: &str) -> SecretVaultResult<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut position = 0;
+
+        while let Some(found) = template[position..].find('$') {
+            let start = position + found;
+            text.push_str(&template[position..start]);
+
+            let rest = &template[start..];
+            if rest.starts_with("$${") {
+                text.push_str("${");
+                position = start + 3;
+            } else if let Some(placeholder) = rest.strip_prefix(SECRET_PLACEHOLDER_PREFIX) {
+                let end = placeholder.find('}').ok_or_else(|| {
+                    template_error(start, "Unterminated secret placeholder, expected '}'")
+                })?;
+                if !text.is_empty() {
+                    parts.push(SecretVaultTemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Self::parse_placeholder(start, &placeholder[..end])?);
+                position = start + SECRET_PLACEHOLDER_PREFIX.len() + end + 1;
+            } else {
+                text.push('$');
+                position = start + 1;
+            }
+        }
+
+        text.push_str(&template[position..]);
+        if !text.is_empty() {
+            parts.push(SecretVaultTemplatePart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> SecretVaultResult<Self> {
+        let path = path.as_ref();
+        let template = std::fs::read_to_string(path).map_err(|err| {
+            SecretVaultInvalidParametersError::create(
+                &path.display().to_string(),
+                &format!("Unable to read template file: {err}"),
+            )
+        })?;
+        Self::parse(&template)
+    }
+
+    fn parse_placeholder(
+        position: usize,
+        placeholder: &str,
+    ) -> SecretVaultResult<SecretVaultTemplatePart> {
+        let (secret_ref, field) = match placeholder.split_once('#') {
+            Some((secret_ref, field)) => (secret_ref, Some(field)),
+            None => (placeholder, None),
+        };
+
+        let secret_ref: SecretVaultRef = secret_ref
+            .trim()
+            .parse()
+            .map_err(|_| template_error(position, "Invalid secret ref in placeholder"))?;
+
+        let field = match field.map(|field| field.trim()) {
+            Some("") => return Err(template_error(position, "Empty secret field name")),
+            #[cfg(not(feature = "serde"))]
+            Some(_) => {
+                return Err(template_error(
+                    position,
+                    "Secret fields require the serde feature",
+                ))
+            }
+            field => field.map(|field| field.to_string()),
+        };
+
+        Ok(SecretVaultTemplatePart::Secret { secret_ref, field })
+    }
+
+    /// Secret refs used in the template to register them in a vault.
+    pub fn secret_refs(&self) -> Vec<SecretVaultRef> {
+        let mut secret_refs: Vec<SecretVaultRef> = Vec::new();
+        for part in self.parts.iter() {
+            if let SecretVaultTemplatePart::Secret { secret_ref, .. } = part {
+                if !secret_refs.contains(secret_ref) {
+                    secret_refs.push(secret_ref.clone());
+                }
+            }
+        }
+        secret_refs
+    }
+
+    /// Renders the template. All secrets in the template are required.
+    pub async fn render<V>(&self, view: &V) -> SecretVaultResult<Zeroizing<String>>
+    where
+        V: SecretVaultView + Send + Sync,
+    {
+        let mut rendered_parts: Vec<Zeroizing<String>> = Vec::with_capacity(self.parts.len());
+
+        for part in self.parts.iter() {
+            match part {
+                SecretVaultTemplatePart::Text(text) => {
+                    rendered_parts.push(Zeroizing::new(text.clone()))
+                }
+                SecretVaultTemplatePart::Secret { secret_ref, field } => {
+                    let rendered = view
+                        .with_secret_by_ref(secret_ref, |secret_value, _| {
+                            render_secret_value(secret_value, field.as_deref())
+                        })
+                        .await?
+                        .ok_or_else(|| {
+                            SecretVaultError::DataNotFoundError(SecretVaultDataNotFoundError::new(
+                                SecretVaultErrorPublicGenericDetails::new(
+                                    "SECRET_NOT_FOUND".into(),
+                                ),
+                                format!(
+                                    "Secret {secret_ref:?} doesn't exist in vault but was required"
+                                ),
+                            ))
+                        })?
+                        .map_err(|message| {
+                            SecretVaultDecodeError::create(&secret_ref.key, &message)
+                        })?;
+                    rendered_parts.push(rendered);
+                }
+            }
+        }
+
+        // Allocating the result once to avoid leaving copies of secrets in reallocated buffers
+        let mut result = Zeroizing::new(String::with_capacity(
+            rendered_parts.iter().map(|part| part.len()).sum(),
+        ));
+        for part in rendered_parts.iter() {
+            result.push_str(part);
+        }
+
+        Ok(result)
+    }
+
+    /// Renders the template and re-renders it each time the vault signals changes
+    /// (see `SecretVault::subscribe_changes`). `on_render` is called with the initial result,
+    /// when the rendered result changes and on errors. The task finishes when the vault is dropped.
+    pub fn render_on_changes<V, F>(
+        self,
+        view: V,
+        mut changes: watch::Receiver<u64>,
+        mut on_render: F,
+    ) -> JoinHandle<()>
+    where
+        V: SecretVaultView + Send + Sync + 'static,
+        F: FnMut(SecretVaultResult<Zeroizing<String>>) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut last_rendered: Option<Zeroizing<String>> = None;
+            loop {
+                changes.borrow_and_update();
+                match self.render(&view).await {
+                    Ok(rendered) => {
+                        if last_rendered.as_ref() != Some(&rendered) {
+                            last_rendered = Some(rendered.clone());
+                            on_render(Ok(rendered));
+                        } else {
+                            trace!("Secret vault template hasn't changed after vault updates");
+                        }
+                    }
+                    Err(err) => {
+                        error!("Unable to render secret vault template: {}", err);
+                        on_render(Err(err));
+                    }
+                }
+
+                if changes.changed().await.is_err() {
+                    debug!("Secret vault has been dropped. Stopping rendering the template");
+                    break;
+                }
+            }
+        })
+    }
+}
+
+fn template_error(position: usize, error: &str) -> SecretVaultError {
+    SecretVaultInvalidParametersError::create(&format!("template[{position}]"), error)
+}
+
+fn render_secret_value(
+    secret_value: &SecretValue,
+    field: Option<&str>,
+) -> Result<Zeroizing<String>, String> {
+    match field {
+        None => secret_value
+            .sensitive_value_to_str()
+            .map(|value| Zeroizing::new(value.to_string()))
+            .map_err(|err| format!("Invalid UTF-8 at position {}", err.valid_up_to())),
+        #[cfg(feature = "serde")]
+        Some(field) => {
+            let mut fields: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_slice(secret_value.as_sensitive_bytes())
+                    .map_err(|_| "Secret is not a valid JSON object".to_string())?;
+            match fields.remove(field) {
+                Some(serde_json::Value::String(value)) => Ok(Zeroizing::new(value)),
+                Some(serde_json::Value::Null) | None => {
+                    Err(format!("Secret doesn't contain field '{field}'"))
+                }
+                Some(value) => Ok(Zeroizing::new(value.to_string())),
+            }
+        }
+        #[cfg(not(feature = "serde"))]
+        Some(_) => Err("Secret fields require the serde feature".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_template_test() {
+        let template = SecretVaultTemplate::parse(
+            "url=postgres://app:${secret:db-password}@db/$${HOME} ${secret:aws/api-key@v1} $5",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.secret_refs(),
+            vec![
+                SecretVaultRef::new("db-password".into()),
+                SecretVaultRef::new("api-key".into())
+                    .with_namespace("aws".into())
+                    .with_secret_version("v1".into())
+            ]
+        );
+        assert_eq!(
+            template.parts.last(),
+            Some(&SecretVaultTemplatePart::Text(" $5".into()))
+        );
+
+        for invalid_template in ["${secret:db-password", "${secret:aws/}", "${secret:test#}"] {
+            match SecretVaultTemplate::parse(invalid_template) {
+                Err(SecretVaultError::InvalidParametersError(err)) => {
+                    assert_eq!(err.public.field, "template[0]")
+                }
+                other => panic!("Unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn render_template_test() {
+        let secret_ref = SecretVaultRef::new("db-password".into()).with_namespace("aws".into());
+        let mock_secrets_store = MockSecretsSource::new(vec![(
+            secret_ref.clone(),
+            SecretValue::from("test-password"),
+        )]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&secret_ref])
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        let template =
+            SecretVaultTemplate::parse("url=postgres://app:${secret:aws/db-password}@db").unwrap();
+        assert_eq!(template.secret_refs(), vec![secret_ref]);
+        assert_eq!(
+            template.render(&vault.viewer()).await.unwrap().as_str(),
+            "url=postgres://app:test-password@db"
+        );
+
+        assert!(SecretVaultTemplate::parse("${secret:unknown-secret}")
+            .unwrap()
+            .render(&vault)
+            .await
+            .is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn render_json_fields_and_changes_test() {
+        let secret_ref = SecretVaultRef::new("api-credentials".into());
+        let mut mock_secrets_store = MockSecretsSource::new(vec![(
+            secret_ref.clone(),
+            SecretValue::from(r#"{"key":"test-key","port":8080}"#),
+        )]);
+
+        let vault = SecretVaultBuilder::with_source(mock_secrets_store.clone())
+            .with_secret_refs(vec![&secret_ref])
+            .build()
+            .unwrap();
+        vault.refresh().await.unwrap();
+
+        let template = SecretVaultTemplate::parse(
+            "${secret:api-credentials#key}:${secret:api-credentials#port}",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&vault).await.unwrap().as_str(),
+            "test-key:8080"
+        );
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = template.render_on_changes(
+            vault.viewer(),
+            vault.subscribe_changes(),
+            move |rendered| {
+                tx.send(rendered.unwrap()).ok();
+            },
+        );
+        assert_eq!(rx.recv().await.unwrap().as_str(), "test-key:8080");
+
+        mock_secrets_store.add(
+            secret_ref.clone(),
+            SecretValue::from(r#"{"key":"new-key","port":8080}"#),
+        );
+        vault.refresh().await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().as_str(), "new-key:8080");
+
+        drop(vault);
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! Tracing spans for following refreshes end to end (e.g. in OpenTelemetry traces).
+//! Spans carry sources and secret identifiers only, never secret values.
+
+use crate::*;
+use rvstruct::ValueStruct;
+use std::future::Future;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::*;
+
+/// A vault refresh. `mode` is `all`, `auto_refresh` or `not_present`.
+pub(crate) fn refresh_span(source: &str, mode: &'static str, secrets: usize) -> Span {
+    info_span!(
+        "secret_vault.refresh",
+        source,
+        mode,
+        secrets,
+        duration_ms = Empty
+    )
+}
+
+/// Reading a batch of secrets from a source. `found` is recorded when the source succeeds.
+pub(crate) fn source_span(source: &str, secrets: usize) -> Span {
+    info_span!(
+        "secret_vault.source.get_secrets",
+        source,
+        secrets,
+        found = Empty,
+        duration_ms = Empty
+    )
+}
+
+/// Reading a single secret in a source.
+pub(crate) fn source_secret_span(source: &'static str, secret_vault_key: &SecretVaultKey) -> Span {
+    debug_span!(
+        "secret_vault.source.get_secret",
+        source,
+        secret_name = secret_vault_key.secret_name.value().as_str(),
+        namespace = secret_vault_key
+            .namespace
+            .as_ref()
+            .map(|namespace| namespace.value().as_str()),
+        secret_version = secret_vault_key
+            .secret_version
+            .as_ref()
+            .map(|version| version.value().as_str()),
+        duration_ms = Empty
+    )
+}
+
+pub(crate) fn store_span(operation: &'static str, secret_vault_key: &SecretVaultKey) -> Span {
+    debug_span!(
+        "secret_vault.store",
+        operation,
+        secret_name = secret_vault_key.secret_name.value().as_str(),
+        namespace = secret_vault_key
+            .namespace
+            .as_ref()
+            .map(|namespace| namespace.value().as_str()),
+        secret_version = secret_vault_key
+            .secret_version
+            .as_ref()
+            .map(|version| version.value().as_str()),
+        duration_ms = Empty
+    )
+}
+
+/// Encrypting or decrypting a secret with the vault encryption provider.
+pub(crate) fn encryption_span(operation: &'static str, secret_vault_key: &SecretVaultKey) -> Span {
+    debug_span!(
+        "secret_vault.encryption",
+        operation,
+        secret_name = secret_vault_key.secret_name.value().as_str(),
+        namespace = secret_vault_key
+            .namespace
+            .as_ref()
+            .map(|namespace| namespace.value().as_str()),
+        secret_version = secret_vault_key
+            .secret_version
+            .as_ref()
+            .map(|version| version.value().as_str()),
+        duration_ms = Empty
+    )
+}
+
+/// An automatic refresher run. `attempt` counts consecutive runs since the last successful one.
+pub(crate) fn auto_refresh_span(operation: &'static str, attempt: u64) -> Span {
+    info_span!(
+        "secret_vault.auto_refresh",
+        operation,
+        attempt,
+        duration_ms = Empty
+    )
+}
+
+/// Runs the future in the span and records its duration in the `duration_ms` field.
+pub(crate) async fn traced<F: Future>(span: Span, future: F) -> F::Output {
+    let started_at = Instant::now();
+    let output = future.instrument(span.clone()).await;
+    span.record("duration_ms", started_at.elapsed().as_millis() as u64);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secret_vault_value::SecretValue;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    type SpanFields = (String, Vec<(String, String)>);
+
+    #[derive(Clone, Default)]
+    struct SpanFieldsCollector {
+        spans: Arc<Mutex<Vec<SpanFields>>>,
+    }
+
+    struct SpanFieldsVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl Visit for SpanFieldsVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl<S> Layer<S> for SpanFieldsCollector
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut SpanFieldsVisitor(&mut fields));
+            self.spans
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name().to_string(), fields));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let mut fields = Vec::new();
+            values.record(&mut SpanFieldsVisitor(&mut fields));
+            let span_name = ctx
+                .span(id)
+                .map(|span| span.name().to_string())
+                .unwrap_or_default();
+            self.spans.lock().unwrap().push((span_name, fields));
+        }
+    }
+
+    #[test]
+    fn spans_dont_contain_secret_values_test() {
+        let secrets_dir = tempfile::tempdir().unwrap();
+        let secret_value = "test-secret-value-7f3a";
+        std::fs::write(secrets_dir.path().join("secret1"), secret_value).unwrap();
+
+        let collector = SpanFieldsCollector::default();
+        let subscriber = tracing_subscriber::registry().with(collector.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let secret_ref = SecretVaultRef::new("secret1".into());
+                    let vault = SecretVaultBuilder::with_source(FilesSource::with_options(
+                        FilesSourceOptions::new().with_root_path(secrets_dir.path().into()),
+                    ))
+                    .with_secret_refs(vec![&secret_ref])
+                    .build()
+                    .unwrap();
+
+                    vault.refresh().await.unwrap();
+                    assert_eq!(
+                        vault
+                            .get_secret_by_ref(&secret_ref)
+                            .await
+                            .unwrap()
+                            .unwrap()
+                            .value,
+                        SecretValue::from(secret_value)
+                    );
+                })
+        });
+
+        let spans = collector.spans.lock().unwrap().clone();
+        for span_name in [
+            "secret_vault.refresh",
+            "secret_vault.source.get_secrets",
+            "secret_vault.source.get_secret",
+            "secret_vault.store",
+            "secret_vault.encryption",
+        ] {
+            assert!(
+                spans.iter().any(|(name, _)| name == span_name),
+                "Span {span_name} wasn't recorded"
+            );
+        }
+        assert!(spans
+            .iter()
+            .any(|(name, fields)| name == "secret_vault.encryption"
+                && fields.contains(&("secret_name".to_string(), "secret1".to_string()))));
+        assert!(spans
+            .iter()
+            .any(|(name, fields)| name == "secret_vault.refresh"
+                && fields.iter().any(|(field, _)| field == "duration_ms")));
+
+        for (name, fields) in spans.iter() {
+            for (field, value) in fields.iter() {
+                assert!(
+                    !value.contains(secret_value),
+                    "Span {name} field {field} contains the secret value"
+                );
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::errors::*;
 use crate::vault_store::SecretVaultStore;
 use crate::*;
 use async_trait::async_trait;
+use secret_vault_value::SecretValue;
 use std::sync::Arc;
 
 #[async_trait]
@@ -55,6 +56,140 @@ pub trait SecretVaultView {
         &self,
         secret_ref: &SecretVaultRef,
     ) -> SecretVaultResult<Option<Secret>>;
+
+    /// Provides access to a secret without cloning it. Any temporary decrypted buffers
+    /// are zeroized after `f` returns.
+    async fn with_secret<F, R>(
+        &self,
+        secret_name: &SecretName,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        Self: Sized,
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R + Send,
+        R: Send,
+    {
+        self.with_secret_by_ref(&SecretVaultRef::new(secret_name.clone()), f)
+            .await
+    }
+
+    async fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        Self: Sized,
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R + Send,
+        R: Send,
+    {
+        Ok(self
+            .get_secret_by_ref(secret_ref)
+            .await?
+            .map(|secret| f(&secret.value, &secret.metadata)))
+    }
+
+    /// Decodes a secret into a typed value using `SecretDecode`.
+    async fn get_secret_as<T: SecretDecode>(
+        &self,
+        secret_name: &SecretName,
+    ) -> SecretVaultResult<Option<Arc<T>>>
+    where
+        Self: Sized,
+    {
+        self.get_secret_as_by_ref(&SecretVaultRef::new(secret_name.clone()))
+            .await
+    }
+
+    async fn require_secret_as<T: SecretDecode>(
+        &self,
+        secret_name: &SecretName,
+    ) -> SecretVaultResult<Arc<T>>
+    where
+        Self: Sized,
+    {
+        self.require_secret_as_by_ref(&SecretVaultRef::new(secret_name.clone()))
+            .await
+    }
+
+    async fn require_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Arc<T>>
+    where
+        Self: Sized,
+    {
+        match self.get_secret_as_by_ref(secret_ref).await? {
+            Some(decoded) => Ok(decoded),
+            None => Err(SecretVaultError::DataNotFoundError(
+                SecretVaultDataNotFoundError::new(
+                    SecretVaultErrorPublicGenericDetails::new("SECRET_NOT_FOUND".into()),
+                    format!("Secret {secret_ref:?} doesn't exist in vault but was required"),
+                ),
+            )),
+        }
+    }
+
+    async fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>>
+    where
+        Self: Sized,
+    {
+        self.with_secret_by_ref(secret_ref, |secret_value, _| {
+            decode_secret_value::<T>(&secret_ref.key, secret_value).map(Arc::new)
+        })
+        .await?
+        .transpose()
+    }
+
+    /// Returns the current version of a secret first, followed by the retained previous versions.
+    async fn get_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<Secret>> {
+        Ok(self
+            .get_secret_by_ref(secret_ref)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns metadata of a secret. Implementations backed by a store don't decrypt the value.
+    async fn get_secret_metadata_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<SecretMetadata>> {
+        Ok(self
+            .get_secret_by_ref(secret_ref)
+            .await?
+            .map(|secret| secret.metadata))
+    }
+
+    async fn list_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<SecretMetadata>> {
+        Ok(self
+            .get_secret_versions_by_ref(secret_ref)
+            .await?
+            .into_iter()
+            .map(|secret| secret.metadata)
+            .collect())
+    }
+
+    async fn get_retained_secret_version_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+        secret_version: &SecretVersion,
+    ) -> SecretVaultResult<Option<Secret>> {
+        Ok(self
+            .get_secret_versions_by_ref(secret_ref)
+            .await?
+            .into_iter()
+            .find(|secret| secret.metadata.version.as_ref() == Some(secret_version)))
+    }
 }
 
 #[derive(Clone)]
@@ -63,6 +198,8 @@ where
     E: SecretVaultEncryption,
 {
     store: Arc<SecretVaultStore<E>>,
+    principal: Option<SecretVaultPrincipal>,
+    policy: Option<Arc<SecretVaultAccessPolicy>>,
 }
 
 impl<E> SecretVaultViewer<E>
@@ -70,7 +207,48 @@ where
     E: SecretVaultEncryption,
 {
     pub fn new(store: Arc<SecretVaultStore<E>>) -> Self {
-        Self { store }
+        Self {
+            store,
+            principal: None,
+            policy: None,
+        }
+    }
+
+    /// Records the principal in audit events for reads through this viewer.
+    pub fn with_principal(self, principal: SecretVaultPrincipal) -> Self {
+        Self {
+            principal: Some(principal),
+            ..self
+        }
+    }
+
+    /// Restricts secrets available through this viewer. Other secrets return `AccessDeniedError`.
+    pub fn with_policy(self, policy: SecretVaultAccessPolicy) -> Self {
+        Self {
+            policy: Some(Arc::new(policy)),
+            ..self
+        }
+    }
+
+    async fn check_access(&self, secret_vault_key: &SecretVaultKey) -> SecretVaultResult<()> {
+        let allowed = match &self.policy {
+            None => true,
+            Some(policy) if policy.allows_key(secret_vault_key) => true,
+            Some(policy) if policy.requires_metadata() => self
+                .store
+                .get_metadata(secret_vault_key)
+                .await
+                .is_some_and(|metadata| policy.allows_metadata(&metadata)),
+            Some(_) => false,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            self.store
+                .audit_access_denied(secret_vault_key, self.principal.as_ref());
+            Err(SecretVaultAccessDeniedError::create(secret_vault_key))
+        }
     }
 }
 
@@ -83,6 +261,67 @@ where
         &self,
         secret_ref: &SecretVaultRef,
     ) -> SecretVaultResult<Option<Secret>> {
-        self.store.get_secret(&secret_ref.key).await
+        self.check_access(&secret_ref.key).await?;
+        let secret = self.store.get_secret(&secret_ref.key).await?;
+        self.store
+            .audit_access(&secret_ref.key, self.principal.as_ref(), secret.is_some());
+        Ok(secret)
+    }
+
+    async fn with_secret_by_ref<F, R>(
+        &self,
+        secret_ref: &SecretVaultRef,
+        f: F,
+    ) -> SecretVaultResult<Option<R>>
+    where
+        F: FnOnce(&SecretValue, &SecretMetadata) -> R + Send,
+        R: Send,
+    {
+        self.check_access(&secret_ref.key).await?;
+        let result = self.store.with_secret(&secret_ref.key, f).await?;
+        self.store
+            .audit_access(&secret_ref.key, self.principal.as_ref(), result.is_some());
+        Ok(result)
+    }
+
+    async fn get_secret_as_by_ref<T: SecretDecode>(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<Arc<T>>> {
+        self.check_access(&secret_ref.key).await?;
+        let decoded = self.store.get_secret_as(&secret_ref.key).await?;
+        self.store
+            .audit_access(&secret_ref.key, self.principal.as_ref(), decoded.is_some());
+        Ok(decoded)
+    }
+
+    async fn get_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<Secret>> {
+        self.check_access(&secret_ref.key).await?;
+        let secrets = self.store.get_secret_versions(&secret_ref.key).await?;
+        self.store.audit_access(
+            &secret_ref.key,
+            self.principal.as_ref(),
+            !secrets.is_empty(),
+        );
+        Ok(secrets)
+    }
+
+    async fn get_secret_metadata_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Option<SecretMetadata>> {
+        self.check_access(&secret_ref.key).await?;
+        Ok(self.store.get_metadata(&secret_ref.key).await)
+    }
+
+    async fn list_secret_versions_by_ref(
+        &self,
+        secret_ref: &SecretVaultRef,
+    ) -> SecretVaultResult<Vec<SecretMetadata>> {
+        self.check_access(&secret_ref.key).await?;
+        Ok(self.store.get_versions_metadata(&secret_ref.key).await)
     }
 }